@@ -13,9 +13,22 @@ fn simple_benchmarks(c: &mut Criterion) {
 fn complex_benchmarks(c: &mut Criterion) {
     c.bench_function("Water Spawner", |b| b.iter(|| black_box(water_spawner())));
     c.bench_function("Water Plinko", |b| b.iter(|| black_box(water_plinko())));
+
+    #[cfg(feature = "parallel")]
+    c.bench_function("Water Spawner Parallel", |b| {
+        b.iter(|| black_box(water_spawner_with(Cells::par_update_all)))
+    });
+    #[cfg(feature = "parallel")]
+    c.bench_function("Water Plinko Parallel", |b| {
+        b.iter(|| black_box(water_plinko_with(Cells::par_update_all)))
+    });
+}
+
+fn water_plinko() -> Cells {
+    water_plinko_with(Cells::update_all)
 }
 
-fn water_plinko() {
+fn water_plinko_with(update: impl Fn(&mut Cells)) -> Cells {
     let mut cells = Cells::new();
 
     for y in 10..100 {
@@ -30,20 +43,28 @@ fn water_plinko() {
             cells.set_cell(point(x, 0), cell(Water));
         }
 
-        cells.update_all();
+        update(&mut cells);
     }
+
+    cells
 }
 
-fn water_spawner() {
+fn water_spawner() -> Cells {
+    water_spawner_with(Cells::update_all)
+}
+
+fn water_spawner_with(update: impl Fn(&mut Cells)) -> Cells {
     let mut cells = Cells::new();
 
     for _ in 0..1000 {
         cells.set_cell(point(0, 0), cell(Water));
-        cells.update_all();
+        update(&mut cells);
     }
+
+    cells
 }
 
-fn single_water() {
+fn single_water() -> Cells {
     let mut cells = Cells::new();
 
     cells.set_cell(point(0, 0), cell(Water));
@@ -51,14 +72,18 @@ fn single_water() {
     for _ in 0..10000 {
         cells.update_all();
     }
+
+    cells
 }
 
-fn empty_world() {
+fn empty_world() -> Cells {
     let mut cells = Cells::new();
 
     for _ in 0..10000 {
         cells.update_all();
     }
+
+    cells
 }
 
 criterion_group!(simple, simple_benchmarks);
@@ -0,0 +1,202 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::{
+    cells::{CellKind, Cells, cell},
+    point::{Rect, point},
+    store::CellStore,
+};
+
+/// Which character stands for which `CellKind` in an ASCII picture of a world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Legend {
+    entries: Vec<(char, CellKind)>,
+}
+
+impl Default for Legend {
+    /// A space for air, `#` for bedrock, `~` for water, `.` for sand and `h` for hydrogen,
+    /// with the rest of the kinds given a letter or symbol of their own.
+    fn default() -> Self {
+        Legend::new([
+            (' ', CellKind::Air),
+            ('#', CellKind::Bedrock),
+            ('~', CellKind::Water),
+            ('.', CellKind::Sand),
+            ('h', CellKind::Hydrogen),
+            ('=', CellKind::Honey),
+            ('p', CellKind::PinkSand),
+            ('u', CellKind::PurpleSand),
+            ('b', CellKind::BlueSand),
+            ('i', CellKind::Ice),
+            ('s', CellKind::Steam),
+            ('w', CellKind::WetSand),
+        ])
+    }
+}
+
+impl Legend {
+    pub fn new(entries: impl IntoIterator<Item = (char, CellKind)>) -> Legend {
+        Legend {
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    /// Adds `symbol` for `kind`, taking over from any earlier entry for the same character.
+    pub fn with(mut self, symbol: char, kind: CellKind) -> Legend {
+        self.entries.retain(|&(existing, _)| existing != symbol);
+        self.entries.push((symbol, kind));
+        self
+    }
+
+    pub fn kind_of(&self, symbol: char) -> Option<CellKind> {
+        self.entries
+            .iter()
+            .find(|&&(entry, _)| entry == symbol)
+            .map(|&(_, kind)| kind)
+    }
+
+    /// The first character given for `kind`, if any.
+    pub fn symbol_of(&self, kind: CellKind) -> Option<char> {
+        self.entries
+            .iter()
+            .find(|&&(_, entry)| entry == kind)
+            .map(|&(symbol, _)| symbol)
+    }
+}
+
+/// Drawn for kinds that are missing from the legend.
+const UNKNOWN: char = '?';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiError {
+    /// A character the legend does not know, `line` and `column` count from one at the top left.
+    UnknownSymbol {
+        symbol: char,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsciiError::UnknownSymbol {
+                symbol,
+                line,
+                column,
+            } => write!(
+                f,
+                "{symbol:?} at line {line}, column {column} is not in the legend"
+            ),
+        }
+    }
+}
+
+impl Error for AsciiError {}
+
+/// The rows of a picture from top to bottom, without the empty first and last lines that come
+/// from writing it as a multi-line string literal.
+fn rows(picture: &str) -> Vec<&str> {
+    let picture = picture.strip_prefix('\n').unwrap_or(picture);
+    let mut rows: Vec<&str> = picture.lines().collect();
+
+    if rows.last().is_some_and(|row| row.trim().is_empty()) {
+        rows.pop();
+    }
+
+    rows
+}
+
+/// The rectangle a picture covers, with its bottom left character at the origin.
+fn picture_rect(rows: &[&str]) -> Rect {
+    let width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    Rect::new(point(0, 0), point(width as i32, rows.len() as i32))
+}
+
+impl Cells {
+    /// Builds a world from a picture with one character per cell, its bottom left character at
+    /// the origin. Short rows are padded with air.
+    ///
+    /// Pictures are mostly test fixtures, so the seed is fixed at 0 and a test that steps the
+    /// world plays out the same on every run.
+    pub fn from_ascii(picture: &str, legend: &Legend) -> Result<Cells, AsciiError> {
+        let rows = rows(picture);
+        let mut cells = Cells::with_seed(0);
+
+        for (line, row) in rows.iter().enumerate() {
+            let y = (rows.len() - 1 - line) as i32;
+
+            for (column, symbol) in row.chars().enumerate() {
+                let kind = legend.kind_of(symbol).ok_or(AsciiError::UnknownSymbol {
+                    symbol,
+                    line: line + 1,
+                    column: column + 1,
+                })?;
+
+                if kind != CellKind::Air {
+                    cells.set_cell(point(column as i32, y), cell(kind));
+                }
+            }
+        }
+
+        Ok(cells)
+    }
+}
+
+impl<S: CellStore> Cells<S> {
+    /// Draws `rect` of the world with one character per cell, top row first.
+    /// Kinds missing from the legend are drawn as `?`.
+    pub fn to_ascii(&self, rect: Rect, legend: &Legend) -> String {
+        let (min_x, min_y) = rect.min().tup();
+        let (max_x, max_y) = rect.max().tup();
+
+        (min_y..max_y)
+            .rev()
+            .map(|y| {
+                (min_x..max_x)
+                    .map(|x| {
+                        let kind = self.cell_at(point(x, y)).kind();
+                        legend.symbol_of(kind).unwrap_or(UNKNOWN)
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Panics with the expected and actual pictures side by side unless the world matches
+    /// `expected`, read the same way as `Cells::from_ascii`.
+    #[track_caller]
+    pub fn assert_ascii(&self, expected: &str, legend: &Legend) {
+        let expected_rows = rows(expected);
+        let rect = picture_rect(&expected_rows);
+        let width = rect.width() as usize;
+        let air = legend.symbol_of(CellKind::Air).unwrap_or(' ');
+
+        let actual = self.to_ascii(rect, legend);
+
+        let mut matches = true;
+        let column = width.max("expected".len());
+        let mut report = format!("{:column$} | actual\n", "expected");
+
+        for (expected, actual) in expected_rows.iter().zip(actual.lines()) {
+            let padding = width - expected.chars().count();
+            let expected = format!("{expected}{}", air.to_string().repeat(padding));
+            let marker = if expected == actual {
+                ""
+            } else {
+                matches = false;
+                " <"
+            };
+            report += &format!("{expected:column$} | {actual}{marker}\n");
+        }
+
+        assert!(matches, "world does not match the picture\n{report}");
+    }
+}
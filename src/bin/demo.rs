@@ -1,4 +1,7 @@
-use std::{rc::Rc, time::Instant};
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use macroquad::prelude::*;
 
@@ -44,6 +47,14 @@ async fn main() {
 
     let mut cells = Cells::new();
 
+    // A material config can be given as the first argument to retune or add materials.
+    if let Some(path) = std::env::args().nth(1) {
+        let text = std::fs::read_to_string(&path).expect("could not read the material config");
+        if let Err(error) = cells.load_materials(&text) {
+            panic!("{path}: {error}");
+        }
+    }
+
     for y in 10..100 {
         for x in -50..50 {
             let offset = y % 2;
@@ -108,8 +119,8 @@ async fn main() {
         }
 
         let timer = Instant::now();
-        cells.update_all();
-        trace!("cells update_all: {}", timer.elapsed().as_micros());
+        cells.advance(Duration::from_secs_f32(get_frame_time()));
+        trace!("cells advance: {}", timer.elapsed().as_micros());
 
         next_frame().await;
     }
@@ -120,9 +131,8 @@ fn draw(cells: &Cells, frame: &mut [u8], screen_pos: Point) {
         let x = i % WIDTH as usize;
         let y = HEIGHT as usize - (i / WIDTH as usize) - 1;
 
-        let rgba = cells
-            .cell_at(point(x as i32, y as i32) + screen_pos)
-            .colour();
+        let kind = cells.cell_at(point(x as i32, y as i32) + screen_pos).kind();
+        let rgba = cells.materials().colour(kind);
 
         pixel.copy_from_slice(&rgba);
     }
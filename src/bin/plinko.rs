@@ -1,14 +1,27 @@
 use the_pink_sand_simulator::{
-    cells::{CellKind::*, Cells, cell},
-    point::point,
+    cells::{Boundary, CellKind::*, Cells, Replace, cell},
+    point::{Rect, point},
 };
 
 fn main() {
-    water_plinko();
+    let seed = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("seed must be a u64"));
+    water_plinko(seed);
 }
 
-fn water_plinko() {
-    let mut cells = Cells::new();
+fn water_plinko(seed: Option<u64>) {
+    // Water that falls past the bottom of the board is deleted rather than simulated forever.
+    let mut cells = Cells::bounded(
+        Rect::new(point(-128, -1024), point(128, 16)),
+        Boundary::Void,
+    );
+
+    if let Some(seed) = seed {
+        cells.reseed(seed);
+    }
+
+    println!("seed: {}", cells.seed());
 
     for y in 10..100 {
         for x in -50..50 {
@@ -18,9 +31,11 @@ fn water_plinko() {
     }
 
     for _ in 0..1000 {
-        for x in -1..=1 {
-            cells.set_cell(point(x, 0), cell(Water));
-        }
+        cells.fill_rect(
+            Rect::new(point(-1, 0), point(2, 1)),
+            cell(Water),
+            Replace::Any,
+        );
 
         cells.update_all();
     }
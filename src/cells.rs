@@ -1,126 +1,382 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
-use rand::Rng;
 use CellKind::*;
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::material::{ConfigError, Material, Materials, Transition, UpdateContext, World};
 use crate::point::{
-    Point, CLOSED_NEIGHBOURS, DOWN, FALL_SLIDE_LEFT, FALL_SLIDE_RIGHT, FALL_TUMBLE_LEFT, FALL_TUMBLE_RIGHT, LEFT, RIGHT, RISE_SLIDE_LEFT, RISE_SLIDE_RIGHT, SLIDE_LEFT, SLIDE_RIGHT, UP
+    CLOSED_NEIGHBOURS, DOWN, FALL_SLIDE_LEFT, FALL_SLIDE_RIGHT, FALL_TUMBLE_LEFT,
+    FALL_TUMBLE_RIGHT, LEFT, Point, RIGHT, RISE_SLIDE_LEFT, RISE_SLIDE_RIGHT, Rect, SLIDE_LEFT,
+    SLIDE_RIGHT, UP, point,
 };
+use crate::reaction::{Reaction, Reactions};
+use crate::store::{CellStore, ChunkStore, DenseStore};
 
-const GLOBAL_AIR: Cell = Cell {
-    swapped: false,
-    kind: Air,
-};
+mod checked;
+mod events;
+mod fill;
+mod heat;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod save;
+
+pub use checked::CellError;
+pub use events::Event;
+pub use fill::Replace;
+pub use heat::AMBIENT_TEMPERATURE;
+pub use save::{FORMAT_VERSION, LoadError};
+
+const GLOBAL_AIR: Cell = Cell::new(Air);
+
+/// What a `Boundary::Wall` looks like from inside the world.
+const GLOBAL_WALL: Cell = Cell::new(Bedrock);
+
+/// How the edge of a bounded world treats cells that reach it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Everything outside the bounds is solid, so nothing can leave.
+    Wall,
+    /// Everything outside the bounds is air, and cells that move out there are deleted.
+    Void,
+    /// Opposite edges are joined, cells leaving one side come back in on the other.
+    Wrap,
+}
+
+/// Where a point actually lives once the world bounds are taken into account.
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Inside(Point),
+    Wall,
+    Void,
+}
 
 #[derive(Debug, Clone)]
-struct CellData {
-    data: HashMap<Point, Cell>,
-    next_updates: HashSet<Point>,
+struct CellData<S> {
+    store: S,
+    bounds: Option<(Rect, Boundary)>,
+    next_updates: FxHashSet<Point>,
+    /// All simulation randomness is drawn from here so a seed reproduces a world exactly.
+    rng: SmallRng,
+    /// `None` unless someone is recording events.
+    events: Option<Vec<Event>>,
+    materials: Arc<Materials>,
+    reactions: Arc<Reactions>,
+    /// Points whose temperature may still change, see `conduct`.
+    thermal: FxHashSet<Point>,
+    heat_sources: FxHashMap<Point, f32>,
 }
 
-impl CellData {
-    fn new() -> Self {
+impl<S: CellStore> CellData<S> {
+    fn new(store: S, seed: u64, materials: Arc<Materials>) -> Self {
         Self {
-            data: HashMap::new(),
-            next_updates: HashSet::new(),
+            store,
+            bounds: None,
+            next_updates: FxHashSet::default(),
+            rng: SmallRng::seed_from_u64(seed),
+            events: None,
+            materials,
+            reactions: Arc::default(),
+            thermal: FxHashSet::default(),
+            heat_sources: FxHashMap::default(),
+        }
+    }
+
+    #[inline]
+    fn locate(&self, point: Point) -> Location {
+        match self.bounds {
+            None => Location::Inside(point),
+            Some((rect, _)) if rect.contains(point) => Location::Inside(point),
+            Some((_, Boundary::Wall)) => Location::Wall,
+            Some((_, Boundary::Void)) => Location::Void,
+            Some((rect, Boundary::Wrap)) => Location::Inside(rect.wrap(point)),
         }
     }
 
     #[inline]
     pub fn cell_at(&self, point: Point) -> Cell {
-        self.data.get(&point).copied().unwrap_or(GLOBAL_AIR)
+        match self.locate(point) {
+            Location::Inside(point) => self.store.cell_at(point),
+            Location::Wall => GLOBAL_WALL,
+            Location::Void => GLOBAL_AIR,
+        }
     }
 
     /// Constraints
-    /// - `point` must be a non-global-air cell location
+    /// - `point` must be a non-global-air cell location inside the bounds
+    /// - the returned cell must not be changed to or from air
     pub fn cell_at_mut(&mut self, point: Point) -> &mut Cell {
-        self.data
-            .get_mut(&point)
+        self.store
+            .cell_at_mut(point)
             .expect("You must know there is a cell here to use this method")
     }
 
+    /// Writes `cell` without waking anything, cells written outside of the bounds are discarded.
+    fn write(&mut self, point: Point, cell: Cell) {
+        if let Location::Inside(point) = self.locate(point) {
+            self.record_write(point, cell.kind);
+            self.store.set_cell(point, cell);
+            self.track_heat(point);
+        }
+    }
+
     pub fn set_cell(&mut self, point: Point, cell: Cell) {
-        self.data.insert(point, cell);
+        self.write(point, cell);
         self.changed(point);
     }
 
     /// Constraints
-    /// - `from` must be a non-global-air cell location
+    /// - `from` must be a non-global-air cell location inside the bounds
+    /// - `to` must not be in a `Boundary::Wall`
     pub fn swap_cells(&mut self, from: Point, to: Point) {
+        match self.locate(to) {
+            Location::Inside(to) => {
+                self.record_swap(from, to);
+                self.store.swap_cells(from, to);
 
-        let from_cell = self.cell_at(from);
-        let maybe_to_cell = self.data.get(&to);
+                if let Some(cell) = self.store.cell_at_mut(from) {
+                    cell.swapped = true;
+                }
 
-        match maybe_to_cell {
-            Some(&to_cell) => {
-                self.data.insert(from, to_cell);
-                self.cell_at_mut(from).swapped = true;
+                self.track_heat(from);
+                self.track_heat(to);
             }
-            None => {
-                self.data.remove(&from);
-            },
-        };
+            Location::Void => {
+                self.record_write(from, Air);
+                self.store.set_cell(from, GLOBAL_AIR);
+            }
+            Location::Wall => return,
+        }
 
-        self.data.insert(to, from_cell);
         self.changed(to);
         self.changed(from);
     }
 
     pub fn awaken(&mut self, point: Point) -> bool {
-        self.next_updates.insert(point);
+        if let Location::Inside(point) = self.locate(point) {
+            self.next_updates.insert(point);
+        }
+        true
+    }
+
+    /// Keeps the cell at `point` awake if it could still move to one of `offsets` on a later tick,
+    /// otherwise lets it sleep until something changes in its neighbourhood.
+    pub fn rest(&mut self, point: Point, offsets: &[Point]) -> bool {
+        if self.could_move(point, offsets) {
+            self.awaken(point);
+        }
         true
     }
 
+    /// Whether the cell at `from` is lighter than anything at the `offset` points (local to from).
+    /// A failed move into a lighter cell only failed by chance or because it was swapped this tick.
+    fn could_move(&self, from: Point, offsets: &[Point]) -> bool {
+        let density = self.density_at(from);
+        offsets
+            .iter()
+            .any(|&offset| density > self.density_at(from + offset))
+    }
+
+    #[inline]
+    fn density_at(&self, point: Point) -> i32 {
+        self.materials.density(self.cell_at(point).kind)
+    }
+
     pub fn changed(&mut self, point: Point) -> bool {
         for offset in CLOSED_NEIGHBOURS {
-            self.next_updates.insert(point + offset);
-        } 
+            self.awaken(point + offset);
+        }
         true
     }
 
     pub fn try_swap(&mut self, from: Point, to: Point) -> bool {
+        if let Location::Wall = self.locate(to) {
+            return false;
+        }
+
         if self.cell_at(to).not_air() && self.cell_at(to).swapped {
             return false;
         }
 
-        let density_diff = self.cell_at(from).density() - self.cell_at(to).density();
-        if rand::rng().random_range(1..10) <= density_diff {
+        let density_diff = self.density_at(from) - self.density_at(to);
+        if self.rng.random_range(1..10) <= density_diff {
             self.swap_cells(from, to);
             true
         } else {
             false
         }
     }
+}
 
-    /// Try to swap the cell at `from` to the `offset` points (local to from) until a swap succeeds.
-    pub fn multi_try_swap(&mut self, from: Point, offsets: &[Point]) -> bool {
-        for point in offsets {
-            let to = *point + from;
-            if self.try_swap(from, to) {
-                return true;
-            }
-        }
+impl<S: CellStore> World for CellData<S> {
+    fn cell_at(&self, point: Point) -> Cell {
+        CellData::cell_at(self, point)
+    }
+
+    fn set_cell(&mut self, point: Point, cell: Cell) {
+        CellData::set_cell(self, point, cell);
+    }
 
-        false
+    fn try_swap(&mut self, from: Point, to: Point) -> bool {
+        CellData::try_swap(self, from, to)
     }
+
+    fn rest(&mut self, point: Point, offsets: &[Point]) -> bool {
+        CellData::rest(self, point, offsets)
+    }
+
+    fn awaken(&mut self, point: Point) {
+        CellData::awaken(self, point);
+    }
+
+    fn rng(&mut self) -> &mut SmallRng {
+        &mut self.rng
+    }
+}
+
+/// The order `Cells::update_all` visits the points that are awake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Schedule {
+    /// Whatever order the update set happens to hold them in, the cheapest option.
+    #[default]
+    Unordered,
+    /// Row by row from the bottom up, scanning left to right and right to left on alternate ticks.
+    Scanline,
 }
 
 #[derive(Debug, Clone)]
-pub struct Cells {
-    data: CellData,
-    skip: HashSet<Point>,
-    current_updates: HashSet<Point>,
+pub struct Cells<S = ChunkStore> {
+    data: CellData<S>,
+    seed: u64,
+    /// Number of completed `update_all` calls.
+    tick: u64,
+    schedule: Schedule,
+    /// Scan direction of the next `Schedule::Scanline` tick.
+    scan_right: bool,
+    skip: FxHashSet<Point>,
+    work: Vec<Work>,
+    current_updates: FxHashSet<Point>,
+    ordered_updates: Vec<Point>,
+    /// Real time one tick stands for in `advance`.
+    timestep: Duration,
+    /// Most ticks a single `advance` may run.
+    max_catch_up: u32,
+    /// Real time passed to `advance` that has not been simulated yet.
+    lag: Duration,
+}
+
+impl Default for Cells {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Cells {
+    /// Creates an empty world with a random seed, see `seed` to recover it.
     pub fn new() -> Cells {
-        let out = Cells {
-            data: CellData::new(),
-            skip: HashSet::new(),
-            current_updates: HashSet::new(),
-        };
+        Cells::with_seed(rand::rng().random())
+    }
+
+    /// Creates an empty world whose simulation is fully determined by `seed` and the edits made to it.
+    pub fn with_seed(seed: u64) -> Cells {
+        Cells::with_store(ChunkStore::default(), seed)
+    }
+
+    /// Creates an empty world limited to `rect`, with `boundary` deciding what happens at its
+    /// edges. Panics if `rect` is empty, like `with_bounds`.
+    pub fn bounded(rect: Rect, boundary: Boundary) -> Cells {
+        Cells::new().with_bounds(rect, boundary)
+    }
+}
+
+impl Cells<DenseStore> {
+    /// Creates an empty world limited to `rect` that keeps all of its cells in a single grid.
+    pub fn dense(rect: Rect, boundary: Boundary) -> Cells<DenseStore> {
+        Cells::with_store(DenseStore::new(rect), rand::rng().random()).with_bounds(rect, boundary)
+    }
+}
+
+impl<S: CellStore> Cells<S> {
+    /// Creates a world backed by `store`, which may already contain cells.
+    /// Nothing is awake until it is changed or woken.
+    pub fn with_store(store: S, seed: u64) -> Cells<S> {
+        Cells {
+            data: CellData::new(store, seed, Arc::new(Materials::default())),
+            seed,
+            tick: 0,
+            schedule: Schedule::default(),
+            scan_right: true,
+            skip: FxHashSet::default(),
+            work: Vec::new(),
+            current_updates: FxHashSet::default(),
+            ordered_updates: Vec::new(),
+            timestep: Duration::from_secs(1) / 60,
+            max_catch_up: 5,
+            lag: Duration::ZERO,
+        }
+    }
 
-        out
+    /// Limits the world to `rect`, with `boundary` deciding what happens at its edges.
+    ///
+    /// # Panics
+    /// If `rect` is empty.
+    pub fn with_bounds(mut self, rect: Rect, boundary: Boundary) -> Cells<S> {
+        assert!(!rect.is_empty(), "world bounds {rect:?} are empty");
+        self.data.bounds = Some((rect, boundary));
+        self
+    }
+
+    pub fn schedule(&self) -> Schedule {
+        self.schedule
+    }
+
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+
+    /// Number of ticks simulated so far.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    /// Sets how much real time one tick stands for in `advance`, a 60th of a second by default.
+    pub fn set_timestep(&mut self, timestep: Duration) {
+        self.timestep = timestep;
+    }
+
+    /// Sets the most ticks a single `advance` may run, 5 by default.
+    pub fn set_max_catch_up(&mut self, max_catch_up: u32) {
+        self.max_catch_up = max_catch_up;
+    }
+
+    /// The seed the simulation randomness was last started from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the simulation randomness from `seed`, leaving the cells untouched.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.data.rng = SmallRng::seed_from_u64(seed);
+    }
+
+    pub fn bounds(&self) -> Option<(Rect, Boundary)> {
+        self.data.bounds
+    }
+
+    pub fn store(&self) -> &S {
+        &self.data.store
     }
 
     #[inline]
@@ -132,54 +388,352 @@ impl Cells {
         self.data.set_cell(point, cell);
     }
 
+    /// Removes whatever is at `point`, waking its neighbours.
+    pub fn erase(&mut self, point: Point) {
+        self.data.set_cell(point, GLOBAL_AIR);
+    }
+
+    /// Removes everything in `rect`, returning how many cells were removed.
+    pub fn erase_rect(&mut self, rect: Rect) -> usize {
+        self.fill_rect(rect, GLOBAL_AIR, Replace::Any)
+    }
+
+    /// Drops any air the store is holding on to and forgets awake points that have nothing in
+    /// them, for worlds whose store was filled from outside of `Cells`.
+    pub fn compact(&mut self) {
+        self.data.store.compact();
+
+        let store = &self.data.store;
+        self.data
+            .next_updates
+            .retain(|&p| store.cell_at(p).not_air());
+        self.data.thermal.retain(|&p| store.cell_at(p).not_air());
+    }
+
+    /// Every non-air cell in the world, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        self.data.store.iter()
+    }
+
+    /// Every cell of `kind` in the world, in no particular order.
+    pub fn iter_kind(&self, kind: CellKind) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        self.iter().filter(move |(_, cell)| cell.kind == kind)
+    }
+
+    /// Every non-air cell inside `rect`, in no particular order.
+    pub fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        self.data.store.iter_rect(rect)
+    }
+
+    /// The smallest rectangle holding every non-air cell, `None` for an empty world.
+    pub fn occupied_bounds(&self) -> Option<Rect> {
+        self.iter()
+            .map(|(point, _)| point.tup())
+            .fold(None, |bounds, (x, y)| {
+                let ((min_x, min_y), (max_x, max_y)) = bounds.unwrap_or(((x, y), (x, y)));
+                Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+            })
+            .map(|((min_x, min_y), (max_x, max_y))| {
+                Rect::new(point(min_x, min_y), point(max_x + 1, max_y + 1))
+            })
+    }
+
+    /// Number of cells of each kind in the world, air is not counted.
+    pub fn count_kinds(&self) -> HashMap<CellKind, usize> {
+        let mut counts = HashMap::new();
+        for (_, cell) in self.iter() {
+            *counts.entry(cell.kind).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Number of cells of `kind` in the world.
+    pub fn count(&self, kind: CellKind) -> usize {
+        self.iter_kind(kind).count()
+    }
+
+    /// Schedules the cell at `point` for the next update, even if it has fallen asleep.
+    pub fn wake(&mut self, point: Point) {
+        self.data.awaken(point);
+    }
+
+    /// Schedules every cell around `point` for the next update.
+    pub fn wake_around(&mut self, point: Point) {
+        self.data.changed(point);
+    }
+
+    pub fn materials(&self) -> &Materials {
+        &self.data.materials
+    }
+
+    /// Adds `material` to this world, returning the kind that stands for it.
+    pub fn register(&mut self, material: impl Material + 'static) -> CellKind {
+        Arc::make_mut(&mut self.data.materials).register(material)
+    }
+
+    /// Adds the materials in the TOML `text` to this world, see `Materials::load`.
+    pub fn load_materials(&mut self, text: &str) -> Result<Vec<CellKind>, ConfigError> {
+        Arc::make_mut(&mut self.data.materials).load(text)
+    }
+
+    /// The reactions between touching cells in this world, none unless some were added.
+    pub fn reactions(&self) -> &Reactions {
+        &self.data.reactions
+    }
+
+    /// Adds `reaction` to this world, after the ones it already has.
+    pub fn add_reaction(&mut self, reaction: Reaction) {
+        Arc::make_mut(&mut self.data.reactions).add(reaction);
+    }
+
+    /// Replaces every reaction in this world with `reactions`, like `Reactions::built_in`.
+    pub fn set_reactions(&mut self, reactions: Reactions) {
+        self.data.reactions = Arc::new(reactions);
+    }
+
+    /// Number of points that will be visited by the next `update_all`.
+    pub fn awake_count(&self) -> usize {
+        self.data.next_updates.len()
+    }
+
+    /// Runs `ticks` updates.
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.update_all();
+        }
+    }
+
+    /// Runs one update for every `timestep` of real time that has passed, counting `elapsed` and
+    /// whatever was left over from earlier calls, and returns how many ran.
+    ///
+    /// At most `max_catch_up` updates run per call. Time beyond that is dropped, so the world
+    /// slows down rather than falling further behind when updates take longer than real time.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.lag += elapsed;
+
+        let mut ticks = 0;
+        while self.lag >= self.timestep && ticks < self.max_catch_up {
+            self.lag -= self.timestep;
+            ticks += 1;
+        }
+
+        if self.lag >= self.timestep {
+            self.lag = Duration::ZERO;
+        }
+
+        self.step(ticks);
+        ticks
+    }
+
     pub fn update_all(&mut self) {
-        let Cells { data, skip, current_updates } = self;
+        self.tick += 1;
+
+        let Cells {
+            data,
+            schedule,
+            scan_right,
+            skip,
+            work,
+            current_updates,
+            ordered_updates,
+            ..
+        } = self;
 
         std::mem::swap(current_updates, &mut data.next_updates);
         data.next_updates.clear();
 
+        let materials = Arc::clone(&data.materials);
+        let reactions = Arc::clone(&data.reactions);
+
         //updates.shuffle(&mut rand::rng());
 
         skip.clear();
 
-        for point in current_updates.iter().copied() {
-            update_cell(data, skip, point);
+        match schedule {
+            Schedule::Unordered => {
+                for point in current_updates.iter().copied() {
+                    update_cell(data, &materials, &reactions, skip, work, point, None);
+                }
+            }
+            Schedule::Scanline => {
+                ordered_updates.clear();
+                ordered_updates.extend(current_updates.iter().copied());
+                scanline_sort(ordered_updates, *scan_right);
+                *scan_right = !*scan_right;
+
+                for point in ordered_updates.iter().copied() {
+                    update_cell(data, &materials, &reactions, skip, work, point, None);
+                }
+            }
         }
+
+        data.conduct(&materials);
     }
 }
 
-fn update_cell(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    if skip.contains(&point) {
-        return;
-    }
+/// Sorts `points` into rows from the bottom up, each row scanned in the given direction.
+fn scanline_sort(points: &mut [Point], scan_right: bool) {
+    let direction = if scan_right { 1 } else { -1 };
+    points.sort_unstable_by_key(|point| {
+        let (x, y) = point.tup();
+        (y, direction * x)
+    });
+}
+
+/// A unit of pending work in an `update_all` pass.
+#[derive(Debug, Clone, Copy)]
+enum Work {
+    /// Visit a point, scheduling the neighbours its cell wants updated before it moves.
+    Visit(Point),
+    /// Move the cell of `CellKind` visited at this point, once its neighbours are done.
+    Move(Point, CellKind),
+}
+
+/// Updates the cell at `point`, updating what it depends on first (e.g. whatever is below falling
+/// sand) so that it can move into the space they leave.
+///
+/// Uses `work` as an explicit stack rather than recursing so that tall columns or wide lakes of
+/// dependent cells cannot overflow the call stack.
+///
+/// With a `limit`, dependencies outside of it are woken for the next tick instead of updated.
+fn update_cell<S: CellStore>(
+    data: &mut CellData<S>,
+    materials: &Materials,
+    reactions: &Reactions,
+    skip: &mut FxHashSet<Point>,
+    work: &mut Vec<Work>,
+    point: Point,
+    limit: Option<Rect>,
+) {
+    work.push(Work::Visit(point));
+
+    while let Some(next) = work.pop() {
+        match next {
+            Work::Visit(point) => {
+                let Location::Inside(point) = data.locate(point) else {
+                    continue;
+                };
+
+                if limit.is_some_and(|limit| !limit.contains(point)) {
+                    data.awaken(point);
+                    continue;
+                }
 
-    skip.insert(point);
+                if !skip.insert(point) {
+                    continue;
+                }
 
-    if !data.data.contains_key(&point) {
-        return;
+                let cell = data.cell_at(point);
+
+                if cell.is_air() {
+                    continue;
+                }
+
+                data.cell_at_mut(point).swapped = false;
+                data.track_heat(point);
+
+                let material = materials.get(cell.kind);
+                if change_state(data, material, point, cell) || react(data, reactions, point, cell)
+                {
+                    continue;
+                }
+
+                work.push(Work::Move(point, cell.kind));
+                for &offset in material.update_first().iter().rev() {
+                    work.push(Work::Visit(point + offset));
+                }
+            }
+            Work::Move(point, kind) => {
+                materials
+                    .get(kind)
+                    .update(&mut UpdateContext::new(data), point);
+            }
+        }
     }
+}
 
-    data.cell_at_mut(point).swapped = false;
+/// Applies the first of `material`'s transitions that the `cell` at `point` is past once it has
+/// been past it for long enough, returning whether the cell changed.
+fn change_state<S: CellStore>(
+    data: &mut CellData<S>,
+    material: &dyn Material,
+    point: Point,
+    cell: Cell,
+) -> bool {
+    let Some(transition) = material
+        .transitions()
+        .iter()
+        .find(|transition| transition.passed(cell.temperature))
+    else {
+        if cell.transition_ticks > 0 {
+            data.cell_at_mut(point).transition_ticks = 0;
+        }
+        return false;
+    };
 
-    match data.cell_at(point).kind {
-        Water => water_update(data, skip, point),
-        Honey => honey_update(data, skip, point),
-        Sand => sand_update(data, skip, point),
-        PinkSand => pink_sand_update(data, skip, point),
-        Air => (),
-        PurpleSand => purple_sand_update(data, skip, point),
-        BlueSand => blue_sand_update(data, skip, point),
-        Bedrock => (),
-        Hydrogen => hydrogen_update(data, skip, point),
+    if cell.transition_ticks < transition.delay {
+        data.cell_at_mut(point).transition_ticks += 1;
+        data.awaken(point);
+        return false;
     }
+
+    data.set_cell(
+        point,
+        Cell::new(transition.into).with_temperature(cell.temperature),
+    );
+    true
 }
 
-fn hydrogen_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    update_cell(data, skip, point + UP);
-    update_cell(data, skip, point + RIGHT);
-    update_cell(data, skip, point + LEFT);
+/// Reacts the `cell` at `point` with the first neighbour it has a reaction with whose chance comes
+/// up, returning whether it reacted. A cell that could have reacted but did not stays awake to try
+/// again.
+fn react<S: CellStore>(
+    data: &mut CellData<S>,
+    reactions: &Reactions,
+    point: Point,
+    cell: Cell,
+) -> bool {
+    if !reactions.reacts(cell.kind) {
+        return false;
+    }
+
+    let mut could_react = false;
+
+    for offset in CLOSED_NEIGHBOURS {
+        let Location::Inside(other_point) = data.locate(point + offset) else {
+            continue;
+        };
+        if other_point == point {
+            continue;
+        }
+
+        let other = data.store.cell_at(other_point);
+        let Some((reaction, (product, other_product))) = reactions.find(cell.kind, other.kind)
+        else {
+            continue;
+        };
 
-    let pref = rand::rng().random_range(1..=10);
+        could_react = true;
+        if !data.rng.random_bool(reaction.probability) {
+            continue;
+        }
+
+        data.set_cell(point, Cell::new(product).with_temperature(cell.temperature));
+        data.set_cell(
+            other_point,
+            Cell::new(other_product).with_temperature(other.temperature),
+        );
+        return true;
+    }
+
+    if could_react {
+        data.awaken(point);
+    }
+    false
+}
+
+fn hydrogen_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_range(1..=10);
 
     let targets = match pref {
         1..=3 => &RISE_SLIDE_LEFT,
@@ -191,15 +745,11 @@ fn hydrogen_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point)
         _ => panic!(),
     };
 
-    let _ = data.multi_try_swap(point, targets) || data.awaken(point);
+    let _ = data.multi_try_swap(point, targets) || data.rest(point, &CLOSED_NEIGHBOURS);
 }
 
-fn water_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    update_cell(data, skip, point + DOWN);
-    update_cell(data, skip, point + RIGHT);
-    update_cell(data, skip, point + LEFT);
-
-    let pref = rand::rng().random_bool(0.5);
+fn water_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
 
     let targets = if pref {
         FALL_SLIDE_RIGHT
@@ -207,36 +757,68 @@ fn water_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
         FALL_SLIDE_LEFT
     };
 
-    let _ = data.multi_try_swap(point, &targets) || data.awaken(point);
+    let _ = data.multi_try_swap(point, &targets) || data.rest(point, &targets);
 }
-fn honey_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    update_cell(data, skip, point + DOWN);
 
-    let pref = rand::rng().random_bool(0.5);
+/// Honey colder than this is too stiff to spread and heaps up like sand.
+const HONEY_STIFF_BELOW: f32 = 10.0;
 
-    let targets = if pref {
-        FALL_SLIDE_RIGHT
-    } else {
-        FALL_SLIDE_LEFT
+fn honey_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
+    let stiff = data.cell_at(point).temperature < HONEY_STIFF_BELOW;
+
+    let targets: &[Point] = match (stiff, pref) {
+        (false, true) => &FALL_SLIDE_RIGHT,
+        (false, false) => &FALL_SLIDE_LEFT,
+        (true, true) => &FALL_TUMBLE_RIGHT,
+        (true, false) => &FALL_TUMBLE_LEFT,
     };
 
-    if !data.multi_try_swap(point, &targets) {
-        data.awaken(point);
+    if !data.multi_try_swap(point, targets) {
+        data.rest(point, targets);
     }
 }
 
-fn sand_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    update_cell(data, skip, point + DOWN);
+fn ice_update(data: &mut UpdateContext, point: Point) {
+    let _ = data.try_swap(point, point + DOWN) || data.rest(point, &[DOWN]);
+}
+
+/// Wet sand clumps, so it only tumbles sideways some of the time.
+fn wet_sand_update(data: &mut UpdateContext, point: Point) {
+    let tumble = data.rng().random_bool(0.25);
+    let pref = data.rng().random_bool(0.5);
+
+    let targets: &[Point] = match (tumble, pref) {
+        (false, _) => &[DOWN],
+        (true, true) => &FALL_TUMBLE_RIGHT,
+        (true, false) => &FALL_TUMBLE_LEFT,
+    };
+
+    let _ = data.multi_try_swap(point, targets) || data.rest(point, targets);
+}
+
+fn steam_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
 
-    let pref = rand::rng().random_bool(0.5);
+    let targets = if pref {
+        RISE_SLIDE_RIGHT
+    } else {
+        RISE_SLIDE_LEFT
+    };
+
+    let _ = data.multi_try_swap(point, &targets) || data.rest(point, &targets);
+}
+
+fn sand_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
 
     let _ = pref && data.multi_try_swap(point, &FALL_TUMBLE_RIGHT)
         || data.multi_try_swap(point, &FALL_TUMBLE_LEFT)
-        || data.awaken(point);
+        || data.rest(point, &FALL_TUMBLE_LEFT);
 }
 
-fn pink_sand_update(data: &mut CellData, _skip: &mut HashSet<Point>, point: Point) {
-    let pref = rand::rng().random_bool(0.5);
+fn pink_sand_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
 
     if pref {
         data.multi_try_swap(point, &FALL_TUMBLE_RIGHT);
@@ -245,10 +827,8 @@ fn pink_sand_update(data: &mut CellData, _skip: &mut HashSet<Point>, point: Poin
     }
 }
 
-fn purple_sand_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    update_cell(data, skip, point + UP);
-
-    let pref = rand::rng().random_bool(0.5);
+fn purple_sand_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
 
     if pref {
         let _ = data.multi_try_swap(point, &FALL_TUMBLE_RIGHT) || data.try_swap(point, point + UP);
@@ -256,19 +836,21 @@ fn purple_sand_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Poi
         let _ = data.multi_try_swap(point, &FALL_TUMBLE_LEFT) || data.try_swap(point, point + UP);
     }
 }
-fn blue_sand_update(data: &mut CellData, skip: &mut HashSet<Point>, point: Point) {
-    update_cell(data, skip, point + DOWN);
-
-    let pref = rand::rng().random_bool(0.5);
+fn blue_sand_update(data: &mut UpdateContext, point: Point) {
+    let pref = data.rng().random_bool(0.5);
 
     let _ = pref && data.multi_try_swap(point, &FALL_TUMBLE_RIGHT)
         || data.multi_try_swap(point, &FALL_TUMBLE_LEFT)
         || data.try_swap(point, point + UP);
 }
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Cell {
     swapped: bool,
     kind: CellKind,
+    /// Meaningless for air, which is always at `AMBIENT_TEMPERATURE`.
+    temperature: f32,
+    /// Updates spent past the threshold of one of its material's transitions.
+    transition_ticks: u16,
 }
 
 pub fn cell(kind: CellKind) -> Cell {
@@ -276,69 +858,261 @@ pub fn cell(kind: CellKind) -> Cell {
 }
 
 impl Cell {
-    pub fn new(kind: CellKind) -> Cell {
+    pub const fn new(kind: CellKind) -> Cell {
         Cell {
             kind,
             swapped: false,
+            temperature: AMBIENT_TEMPERATURE,
+            transition_ticks: 0,
         }
     }
 
-    pub fn not_air(&self) -> bool {
-        match self.kind {
-            Air => false,
-            _ => true,
+    /// The same cell at `temperature`.
+    pub const fn with_temperature(self, temperature: f32) -> Cell {
+        Cell {
+            temperature,
+            ..self
         }
     }
 
+    pub fn kind(&self) -> CellKind {
+        self.kind
+    }
+
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    pub fn not_air(&self) -> bool {
+        !matches!(self.kind, Air)
+    }
+
     pub fn is_air(&self) -> bool {
-        match self.kind {
-            Air => true,
-            _ => false,
-        }
+        matches!(self.kind, Air)
     }
 
     pub fn is(&self, cells: &[CellKind]) -> bool {
         cells.contains(&self.kind)
     }
+}
 
-    pub fn colour(&self) -> [u8; 4] {
-        match self.kind {
-            Water => [30, 76, 200, 255],
-            Honey => [140, 90, 50, 255],
-            PinkSand => [160, 80, 110, 255],
-            Sand => [200, 100, 50, 255],
-            Air => [200, 200, 235, 255],
-            PurpleSand => [120, 80, 180, 255],
-            BlueSand => [90, 70, 210, 255],
-            Bedrock => [13, 39, 20, 255],
-            Hydrogen => [230, 230, 230, 255],
+macro_rules! define_materials {
+    {$((
+        $name:ident, $code:literal, $display:literal, $colour:expr, $density:expr,
+        $heat_capacity:expr, $conductivity:expr, $first:expr, $update:expr, $transitions:expr,
+        $locked:literal
+    )),+ $(,)?} => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum CellKind {
+            $(
+                $name,
+            )+
+            /// A kind added with `Cells::register`.
+            Custom(u16),
+        }
+
+        /// The built-in kinds on their own, so their position can be taken with `as`.
+        #[derive(Clone, Copy)]
+        enum BuiltInKind {
+            $(
+                $name
+            ),+
+        }
+
+        impl CellKind {
+            /// Every built-in kind.
+            pub const ALL: [CellKind; [$(BuiltInKind::$name),+].len()] = [$(CellKind::$name),+];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(
+                        CellKind::$name => $display,
+                    )+
+                    CellKind::Custom(_) => "Custom",
+                }
+            }
+
+            /// Position of the kind in `Materials`, built-in kinds in the order of `ALL`.
+            pub(crate) fn index(self) -> usize {
+                match self {
+                    $(
+                        CellKind::$name => BuiltInKind::$name as usize,
+                    )+
+                    CellKind::Custom(id) => CellKind::ALL.len() + id as usize,
+                }
+            }
+
+            /// Code of the kind in the save format, registered kinds have none.
+            pub(crate) fn save_code(self) -> Option<u8> {
+                match self {
+                    $(
+                        CellKind::$name => Some($code),
+                    )+
+                    CellKind::Custom(_) => None,
+                }
+            }
+
+            pub(crate) fn from_save_code(code: u8) -> Option<CellKind> {
+                match code {
+                    $(
+                        $code => Some(CellKind::$name),
+                    )+
+                    _ => None,
+                }
+            }
+        }
+
+        impl FromStr for CellKind {
+            type Err = ParseKindError;
+
+            /// Reads a built-in kind from its name or its variant, so both `Pink Sand` and
+            /// `PinkSand` work.
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(
+                    if s == $display || s == stringify!($name) {
+                        return Ok(CellKind::$name);
+                    }
+                )+
+                Err(ParseKindError(s.to_owned()))
+            }
+        }
+
+        /// Colour of the built-in `kind`, as its world's `Materials` starts out.
+        pub(crate) fn colour(kind: CellKind) -> [u8; 4] {
+            match kind {
+                $(
+                    CellKind::$name => $colour,
+                )+
+                CellKind::Custom(_) => [255, 0, 255, 255],
+            }
         }
-    }
 
-    pub fn density(&self) -> i32 {
-        match self.kind {
-            PurpleSand => 30,
-            BlueSand => 30,
-            Water => 25,
-            Honey => 28,
-            Sand => 30,
-            PinkSand => 30,
-            Air => 0,
-            Bedrock => 500,
-            Hydrogen => 5,
+        /// Density of the built-in `kind`, as its world's `Materials` starts out.
+        pub(crate) fn density(kind: CellKind) -> i32 {
+            match kind {
+                $(
+                    CellKind::$name => $density,
+                )+
+                CellKind::Custom(_) => 0,
+            }
+        }
+
+        /// Heat capacity of the built-in `kind`, as its world's `Materials` starts out.
+        pub(crate) fn heat_capacity(kind: CellKind) -> f32 {
+            match kind {
+                $(
+                    CellKind::$name => $heat_capacity,
+                )+
+                CellKind::Custom(_) => 1.0,
+            }
+        }
+
+        /// Conductivity of the built-in `kind`, as its world's `Materials` starts out.
+        pub(crate) fn conductivity(kind: CellKind) -> f32 {
+            match kind {
+                $(
+                    CellKind::$name => $conductivity,
+                )+
+                CellKind::Custom(_) => 0.0,
+            }
+        }
+
+        /// Neighbours (local to the cell) that are updated before a cell of the built-in `kind`
+        /// moves.
+        pub(crate) fn update_first(kind: CellKind) -> &'static [Point] {
+            match kind {
+                $(
+                    CellKind::$name => &$first,
+                )+
+                CellKind::Custom(_) => &[],
+            }
+        }
+
+        /// Changes of state of the built-in `kind`.
+        pub(crate) fn transitions(kind: CellKind) -> &'static [Transition] {
+            match kind {
+                $(
+                    CellKind::$name => const { &$transitions },
+                )+
+                CellKind::Custom(_) => &[],
+            }
+        }
+
+        /// Whether cells of the built-in `kind` are refused by the checked edits.
+        pub(crate) fn locked(kind: CellKind) -> bool {
+            match kind {
+                $(
+                    CellKind::$name => $locked,
+                )+
+                CellKind::Custom(_) => false,
+            }
+        }
+
+        /// Moves the cell of the built-in `kind` at `point`.
+        pub(crate) fn move_cell(world: &mut UpdateContext, point: Point, kind: CellKind) {
+            match kind {
+                $(
+                    CellKind::$name => $update(world, point),
+                )+
+                CellKind::Custom(_) => (),
+            }
+        }
+    };
+}
+
+impl CellKind {
+    pub(crate) fn from_index(index: usize) -> CellKind {
+        match CellKind::ALL.get(index) {
+            Some(&kind) => kind,
+            None => Custom((index - CellKind::ALL.len()) as u16),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum CellKind {
-    PurpleSand,
-    BlueSand,
-    Water,
-    Honey,
-    Sand,
-    PinkSand,
-    Air,
-    Bedrock,
-    Hydrogen,
+impl Display for CellKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A string that is not the name of a built-in kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKindError(pub String);
+
+impl Display for ParseKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a built-in cell kind", self.0)
+    }
+}
+
+impl Error for ParseKindError {}
+
+/// Update for kinds that never move by themselves.
+fn still(_: &mut UpdateContext, _: Point) {}
+
+// One row per built-in kind: variant, save code, name, colour, density, heat capacity,
+// conductivity, the neighbours updated before it moves, its update, its changes of state and
+// whether it is locked. Save codes are part of the file format, so they must never change once
+// released.
+define_materials! {
+    (PurpleSand, 1, "Purple Sand", [120, 80, 180, 255], 30, 1.0, 0.05, [UP], purple_sand_update,
+        [], false),
+    (BlueSand, 2, "Blue Sand", [90, 70, 210, 255], 30, 1.0, 0.05, [DOWN], blue_sand_update, [],
+        false),
+    (Water, 3, "Water", [30, 76, 200, 255], 25, 4.0, 0.15, [DOWN, RIGHT, LEFT], water_update,
+        [Transition::below(0.0, Ice, 20), Transition::above(100.0, Steam, 40)], false),
+    (Honey, 4, "Honey", [140, 90, 50, 255], 28, 2.5, 0.05, [DOWN], honey_update, [], false),
+    (Sand, 5, "Sand", [200, 100, 50, 255], 30, 1.0, 0.05, [DOWN], sand_update, [], false),
+    (PinkSand, 6, "Pink Sand", [160, 80, 110, 255], 30, 1.0, 0.05, [], pink_sand_update, [],
+        false),
+    (Air, 0, "Air", [200, 200, 235, 255], 0, 1.0, 0.005, [], still, [], false),
+    (Bedrock, 7, "Bedrock", [13, 39, 20, 255], 500, 2.0, 0.1, [], still, [], true),
+    (Hydrogen, 8, "Hydrogen", [230, 230, 230, 255], 5, 14.0, 0.2, [UP, RIGHT, LEFT],
+        hydrogen_update, [], false),
+    (Ice, 9, "Ice", [170, 210, 240, 255], 24, 2.0, 0.2, [DOWN], ice_update,
+        [Transition::above(0.0, Water, 20)], false),
+    (Steam, 10, "Steam", [215, 220, 230, 255], 4, 2.0, 0.02, [UP, RIGHT, LEFT], steam_update,
+        [Transition::below(90.0, Water, 30)], false),
+    (WetSand, 11, "Wet Sand", [140, 70, 40, 255], 32, 1.5, 0.1, [DOWN], wet_sand_update,
+        [Transition::above(100.0, Sand, 60)], false),
 }
@@ -0,0 +1,97 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::{Cell, Cells, Location};
+use crate::{point::Point, store::CellStore};
+
+/// Why a checked change to the world was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellError {
+    /// There is only air at the point.
+    Missing(Point),
+    /// The point is outside of bounds that do not wrap.
+    OutOfBounds(Point),
+    /// The cell at the point is of a locked material, like bedrock.
+    Locked(Point),
+}
+
+impl Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellError::Missing(point) => write!(f, "there is no cell at {point:?}"),
+            CellError::OutOfBounds(point) => write!(f, "{point:?} is out of bounds"),
+            CellError::Locked(point) => write!(f, "the cell at {point:?} is locked"),
+        }
+    }
+}
+
+impl Error for CellError {}
+
+impl<S: CellStore> Cells<S> {
+    /// Same as `set_cell`, but refuses points outside of the bounds and locked cells.
+    pub fn try_set(&mut self, point: Point, cell: Cell) -> Result<(), CellError> {
+        let inside = self.check_unlocked(point)?;
+        self.data.set_cell(inside, cell);
+        Ok(())
+    }
+
+    /// Changes the cell at `point` through `update`, writing the result back as `set_cell` would.
+    /// Changing the kind of the cell wakes its neighbourhood, changing anything else wakes nothing.
+    pub fn try_update(
+        &mut self,
+        point: Point,
+        update: impl FnOnce(&mut Cell),
+    ) -> Result<(), CellError> {
+        let inside = self.check_unlocked(point)?;
+
+        let mut cell = self.data.store.cell_at(inside);
+        if cell.is_air() {
+            return Err(CellError::Missing(point));
+        }
+
+        let kind = cell.kind;
+        update(&mut cell);
+
+        if cell.kind == kind {
+            self.data.write(inside, cell);
+        } else {
+            self.data.set_cell(inside, cell);
+        }
+        Ok(())
+    }
+
+    /// Swaps the cell at `from` with whatever is at `to`, waking both.
+    ///
+    /// There must be a cell at `from`, neither point may be out of bounds and neither cell may be
+    /// locked.
+    pub fn try_swap(&mut self, from: Point, to: Point) -> Result<(), CellError> {
+        let inside_from = self.check_unlocked(from)?;
+        let inside_to = self.check_unlocked(to)?;
+
+        if self.data.store.cell_at(inside_from).is_air() {
+            return Err(CellError::Missing(from));
+        }
+
+        self.data.record_swap(inside_from, inside_to);
+        self.data.store.swap_cells(inside_from, inside_to);
+        self.data.changed(inside_from);
+        self.data.changed(inside_to);
+        Ok(())
+    }
+
+    /// The point inside the bounds that `point` refers to, if its cell may be changed.
+    fn check_unlocked(&self, point: Point) -> Result<Point, CellError> {
+        let Location::Inside(inside) = self.data.locate(point) else {
+            return Err(CellError::OutOfBounds(point));
+        };
+
+        let kind = self.data.store.cell_at(inside).kind();
+        if self.data.materials.get(kind).locked() {
+            return Err(CellError::Locked(point));
+        }
+
+        Ok(inside)
+    }
+}
@@ -0,0 +1,100 @@
+use super::{CellData, CellKind, Cells};
+use crate::{point::Point, store::CellStore};
+
+/// Something that happened to the cells of a world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A cell moved into air.
+    Moved {
+        from: Point,
+        to: Point,
+        kind: CellKind,
+    },
+    /// A cell of `kind` moved from `from` to `to` and the cell of `other` kind moved the other way.
+    Swapped {
+        from: Point,
+        to: Point,
+        kind: CellKind,
+        other: CellKind,
+    },
+    /// A cell appeared where there was air.
+    Created { at: Point, kind: CellKind },
+    /// A cell was removed, by being overwritten or by falling into a `Boundary::Void`.
+    Destroyed { at: Point, kind: CellKind },
+}
+
+impl<S: CellStore> Cells<S> {
+    /// Starts recording events for `drain_events`. Worlds that are not recording pay nothing for
+    /// events.
+    pub fn record_events(&mut self) {
+        self.data.events.get_or_insert_with(Vec::new);
+    }
+
+    /// Stops recording events and drops any that were not drained.
+    pub fn stop_recording_events(&mut self) {
+        self.data.events = None;
+    }
+
+    /// Takes every event recorded since the last drain, in the order they happened.
+    ///
+    /// Events build up until they are drained, so drain them after every update.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = Event> + '_ {
+        self.data
+            .events
+            .iter_mut()
+            .flat_map(|events| events.drain(..))
+    }
+}
+
+impl<S: CellStore> CellData<S> {
+    #[inline]
+    fn emit(&mut self, event: Event) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+
+    /// Records writing a cell of `kind` at the in bounds `point`, call before the store is changed.
+    #[inline]
+    pub(super) fn record_write(&mut self, point: Point, kind: CellKind) {
+        if self.events.is_none() {
+            return;
+        }
+
+        let old = self.store.cell_at(point).kind();
+        if old == kind {
+            return;
+        }
+
+        if old != CellKind::Air {
+            self.emit(Event::Destroyed {
+                at: point,
+                kind: old,
+            });
+        }
+        if kind != CellKind::Air {
+            self.emit(Event::Created { at: point, kind });
+        }
+    }
+
+    /// Records swapping the in bounds points `from` and `to`, call before the store is changed.
+    #[inline]
+    pub(super) fn record_swap(&mut self, from: Point, to: Point) {
+        if self.events.is_none() {
+            return;
+        }
+
+        let kind = self.store.cell_at(from).kind();
+        let other = self.store.cell_at(to).kind();
+
+        match other {
+            CellKind::Air => self.emit(Event::Moved { from, to, kind }),
+            _ => self.emit(Event::Swapped {
+                from,
+                to,
+                kind,
+                other,
+            }),
+        }
+    }
+}
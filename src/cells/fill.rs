@@ -0,0 +1,130 @@
+use rustc_hash::FxHashSet;
+
+use super::{Cell, CellKind, Cells, Location};
+use crate::{
+    point::{CLOSED_NEIGHBOURS, DOWN, LEFT, Point, RIGHT, Rect, UP, circle, line},
+    store::CellStore,
+};
+
+/// Which cells a bulk fill is allowed to overwrite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Replace<'a> {
+    #[default]
+    Any,
+    Only(&'a [CellKind]),
+}
+
+impl Replace<'_> {
+    fn allows(self, kind: CellKind) -> bool {
+        match self {
+            Replace::Any => true,
+            Replace::Only(kinds) => kinds.contains(&kind),
+        }
+    }
+}
+
+impl<S: CellStore> Cells<S> {
+    /// Sets every cell in `rect` to `cell`, returning how many cells changed.
+    pub fn fill_rect(&mut self, rect: Rect, cell: Cell, replace: Replace) -> usize {
+        self.fill(rect.points(), cell, replace)
+    }
+
+    /// Sets every cell within `radius` of `centre` to `cell`, returning how many cells changed.
+    pub fn fill_circle(
+        &mut self,
+        centre: Point,
+        radius: i32,
+        cell: Cell,
+        replace: Replace,
+    ) -> usize {
+        self.fill(circle(centre, radius), cell, replace)
+    }
+
+    /// Sets every cell within `radius` of the line from `from` to `to` to `cell`, returning how
+    /// many cells changed. A radius of zero draws a line one cell thick.
+    pub fn draw_line(
+        &mut self,
+        from: Point,
+        to: Point,
+        radius: i32,
+        cell: Cell,
+        replace: Replace,
+    ) -> usize {
+        self.fill(
+            line(from, to).flat_map(|p| circle(p, radius)),
+            cell,
+            replace,
+        )
+    }
+
+    /// Sets the cells of the same kind as the one at `start` that are connected to it through
+    /// their edges to `cell`, returning how many cells changed.
+    ///
+    /// Only cells inside `area` are filled, which stops a fill of air in an unbounded world from
+    /// running forever.
+    pub fn flood_fill(&mut self, start: Point, area: Rect, cell: Cell, replace: Replace) -> usize {
+        let Location::Inside(start) = self.data.locate(start) else {
+            return 0;
+        };
+
+        let target = self.data.store.cell_at(start).kind();
+        if target == cell.kind() || !replace.allows(target) || !area.contains(start) {
+            return 0;
+        }
+
+        let mut region = FxHashSet::default();
+        let mut stack = vec![start];
+        region.insert(start);
+
+        while let Some(p) = stack.pop() {
+            for offset in [UP, DOWN, LEFT, RIGHT] {
+                let Location::Inside(next) = self.data.locate(p + offset) else {
+                    continue;
+                };
+
+                if area.contains(next)
+                    && self.data.store.cell_at(next).kind() == target
+                    && region.insert(next)
+                {
+                    stack.push(next);
+                }
+            }
+        }
+
+        self.fill(region, cell, replace)
+    }
+
+    /// Writes `cell` to every allowed point that does not already hold its kind, then wakes the
+    /// changed cells and their neighbours once each rather than once per write.
+    fn fill(
+        &mut self,
+        points: impl IntoIterator<Item = Point>,
+        cell: Cell,
+        replace: Replace,
+    ) -> usize {
+        let mut changed = FxHashSet::default();
+
+        for p in points {
+            let Location::Inside(p) = self.data.locate(p) else {
+                continue;
+            };
+
+            let kind = self.data.store.cell_at(p).kind();
+            if kind != cell.kind() && replace.allows(kind) {
+                self.data.write(p, cell);
+                changed.insert(p);
+            }
+        }
+
+        for &p in &changed {
+            for offset in CLOSED_NEIGHBOURS {
+                let neighbour = p + offset;
+                if neighbour == p || !changed.contains(&neighbour) {
+                    self.data.awaken(neighbour);
+                }
+            }
+        }
+
+        changed.len()
+    }
+}
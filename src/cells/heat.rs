@@ -0,0 +1,222 @@
+use rustc_hash::FxHashMap;
+
+use super::{CellData, CellError, CellKind, Cells, Location};
+use crate::{
+    material::Materials,
+    point::{DOWN, LEFT, Point, RIGHT, UP},
+    store::CellStore,
+};
+
+/// Temperature of air, and of every cell until something heats or cools it.
+pub const AMBIENT_TEMPERATURE: f32 = 20.0;
+
+/// Most of the difference in temperature that can flow to one neighbour in a tick, keeping
+/// conduction stable whatever the materials.
+const MAX_FLOW: f32 = 0.2;
+
+/// Changes in temperature smaller than this let a point drop out of conduction.
+const SETTLED: f32 = 0.001;
+
+impl<S: CellStore> Cells<S> {
+    /// Temperature at `point`. Air, and anything outside of the bounds, is at
+    /// `AMBIENT_TEMPERATURE` unless there is a heat source there.
+    pub fn temperature_at(&self, point: Point) -> f32 {
+        let Location::Inside(point) = self.data.locate(point) else {
+            return AMBIENT_TEMPERATURE;
+        };
+
+        match self.data.heat_sources.get(&point) {
+            Some(&temperature) => temperature,
+            None => self.data.heat_at(point, &self.data.materials).temperature,
+        }
+    }
+
+    /// Sets the temperature of the cell at `point`, from where it conducts to its neighbours.
+    pub fn set_temperature(&mut self, point: Point, temperature: f32) -> Result<(), CellError> {
+        let Location::Inside(inside) = self.data.locate(point) else {
+            return Err(CellError::OutOfBounds(point));
+        };
+
+        match self.data.store.cell_at_mut(inside) {
+            Some(cell) if cell.not_air() => cell.temperature = temperature,
+            _ => return Err(CellError::Missing(point)),
+        }
+
+        self.data.thermal.insert(inside);
+        self.data.wake_if_changing(inside);
+        Ok(())
+    }
+
+    /// Holds `point` at `temperature` every tick, heating or cooling whatever is there and
+    /// conducting to its neighbours as well as they conduct. Replaces any heat source already at
+    /// `point`, and does nothing outside of the bounds.
+    pub fn add_heat_source(&mut self, point: Point, temperature: f32) {
+        if let Location::Inside(point) = self.data.locate(point) {
+            self.data.heat_sources.insert(point, temperature);
+        }
+    }
+
+    /// Removes the heat source at `point`, returning whether there was one.
+    pub fn remove_heat_source(&mut self, point: Point) -> bool {
+        match self.data.locate(point) {
+            Location::Inside(point) => {
+                // Whatever the source was holding cools or warms from here on.
+                self.data.thermal.insert(point);
+                self.data.heat_sources.remove(&point).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Every heat source and its temperature, in no particular order.
+    pub fn heat_sources(&self) -> impl Iterator<Item = (Point, f32)> + '_ {
+        self.data.heat_sources.iter().map(|(&p, &t)| (p, t))
+    }
+}
+
+/// What conduction needs to know about one point.
+#[derive(Clone, Copy)]
+struct Heat {
+    temperature: f32,
+    /// Infinite for points whose temperature never changes.
+    capacity: f32,
+    conductivity: f32,
+}
+
+impl<S: CellStore> CellData<S> {
+    /// Marks the in bounds `point` for `conduct` if its cell is not at ambient temperature.
+    #[inline]
+    pub(super) fn track_heat(&mut self, point: Point) {
+        let cell = self.store.cell_at(point);
+        if cell.not_air() && cell.temperature != AMBIENT_TEMPERATURE {
+            self.thermal.insert(point);
+        }
+    }
+
+    /// The in bounds `point` as conduction sees it, air being an endless supply of ambient heat.
+    fn heat_at(&self, point: Point, materials: &Materials) -> Heat {
+        let cell = self.store.cell_at(point);
+
+        if cell.is_air() {
+            air_heat(materials)
+        } else {
+            Heat {
+                temperature: cell.temperature,
+                capacity: materials.heat_capacity(cell.kind),
+                conductivity: materials.conductivity(cell.kind),
+            }
+        }
+    }
+
+    /// Moves heat one tick's worth between the points that are warming or cooling and their four
+    /// neighbours, then pins cells on heat sources to the source's temperature.
+    ///
+    /// Only points that changed by more than `SETTLED` last tick, heat sources and cells written
+    /// or moved away from ambient temperature take part, so a world at rest costs nothing here.
+    /// Every exchange is worked out from the temperatures at the start of the tick, so the order
+    /// the points are visited in does not matter.
+    pub(super) fn conduct(&mut self, materials: &Materials) {
+        if self.thermal.is_empty() && self.heat_sources.is_empty() {
+            return;
+        }
+
+        let mut active = std::mem::take(&mut self.thermal);
+        active.extend(self.heat_sources.keys().copied());
+
+        let mut changes: FxHashMap<Point, f32> = FxHashMap::default();
+
+        for &p in &active {
+            if self.store.cell_at(p).is_air() && !self.heat_sources.contains_key(&p) {
+                continue;
+            }
+            let here = self.heat_or_source(p, materials);
+
+            for offset in [UP, RIGHT, DOWN, LEFT] {
+                let (q, there) = match self.locate(p + offset) {
+                    Location::Inside(q) => (q, self.heat_or_source(q, materials)),
+                    Location::Void => (p + offset, air_heat(materials)),
+                    Location::Wall => continue,
+                };
+
+                // Pairs of active points are exchanged once, by the one with the other above or to
+                // its right.
+                let takes_part =
+                    self.store.cell_at(q).not_air() || self.heat_sources.contains_key(&q);
+                if (offset == DOWN || offset == LEFT) && takes_part && active.contains(&q) {
+                    continue;
+                }
+
+                let conductivity = here
+                    .conductivity
+                    .min(there.conductivity)
+                    .min(MAX_FLOW * here.capacity.min(there.capacity));
+                let flow = conductivity * (here.temperature - there.temperature);
+
+                if here.capacity.is_finite() {
+                    *changes.entry(p).or_default() -= flow / here.capacity;
+                }
+                if there.capacity.is_finite() {
+                    *changes.entry(q).or_default() += flow / there.capacity;
+                }
+            }
+        }
+
+        for (p, change) in changes {
+            if let Some(cell) = self.store.cell_at_mut(p) {
+                cell.temperature += change;
+            }
+            if change.abs() > SETTLED {
+                self.thermal.insert(p);
+                self.wake_if_changing(p);
+            }
+        }
+
+        let sources = std::mem::take(&mut self.heat_sources);
+        for (&p, &temperature) in &sources {
+            if let Some(cell) = self.store.cell_at_mut(p)
+                && cell.not_air()
+            {
+                cell.temperature = temperature;
+                self.wake_if_changing(p);
+            }
+        }
+        self.heat_sources = sources;
+    }
+
+    /// Wakes the cell at the in bounds `point` if it is past the threshold of one of its
+    /// material's transitions, as only awake cells change state.
+    fn wake_if_changing(&mut self, point: Point) {
+        let cell = self.store.cell_at(point);
+        let changing = self
+            .materials
+            .get(cell.kind)
+            .transitions()
+            .iter()
+            .any(|transition| transition.passed(cell.temperature));
+
+        if changing {
+            self.next_updates.insert(point);
+        }
+    }
+
+    /// `heat_at`, but a heat source holds its temperature and conducts as well as its neighbours.
+    fn heat_or_source(&self, point: Point, materials: &Materials) -> Heat {
+        match self.heat_sources.get(&point) {
+            Some(&temperature) => Heat {
+                temperature,
+                capacity: f32::INFINITY,
+                conductivity: f32::INFINITY,
+            },
+            None => self.heat_at(point, materials),
+        }
+    }
+}
+
+/// Air, and the air beyond a `Boundary::Void`.
+fn air_heat(materials: &Materials) -> Heat {
+    Heat {
+        temperature: AMBIENT_TEMPERATURE,
+        capacity: f32::INFINITY,
+        conductivity: materials.conductivity(CellKind::Air),
+    }
+}
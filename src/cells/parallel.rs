@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use rand::Rng;
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{Boundary, CellData, Cells, Location, Schedule, scanline_sort, update_cell};
+use crate::{
+    point::{Point, Rect, point},
+    store::{CellStore, DenseStore},
+};
+
+/// Side length of the square tiles the world is split into for a parallel update.
+const TILE_SIZE: i32 = 64;
+
+/// The four passes of the checkerboard, as the parity of the tile coordinates updated in each.
+const PASSES: [(i32, i32); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+/// The awake points of one tile, with everything needed to update it on its own thread.
+struct Tile {
+    rect: Rect,
+    points: Vec<Point>,
+    seed: u64,
+}
+
+impl<S: CellStore + Send + Sync> Cells<S> {
+    /// Same as `update_all`, but splits the awake points into tiles and updates them across threads.
+    ///
+    /// Tiles are updated in four checkerboard passes, so the tiles in a pass are never adjacent and
+    /// each one can work on a private copy of itself plus a one cell border that no other tile in
+    /// the pass can reach. A cell that wants a neighbour in another tile updated before it moves
+    /// gets it updated next tick instead.
+    ///
+    /// Worlds with a `Boundary::Wrap` are updated with `update_all`, as a tile on one edge can reach
+    /// a tile on the opposite edge in the same pass.
+    pub fn par_update_all(&mut self) {
+        if let Some((_, Boundary::Wrap)) = self.data.bounds {
+            self.update_all();
+            return;
+        }
+
+        self.tick += 1;
+
+        let mut tiles: FxHashMap<Point, Vec<Point>> = FxHashMap::default();
+        for p in self.data.next_updates.drain() {
+            let (x, y) = p.tup();
+            let coords = point(x.div_euclid(TILE_SIZE), y.div_euclid(TILE_SIZE));
+            tiles.entry(coords).or_default().push(p);
+        }
+
+        // Tile seeds are handed out in coordinate order so the result does not depend on threads.
+        let mut tiles: Vec<_> = tiles.into_iter().collect();
+        tiles.sort_unstable_by_key(|(coords, _)| coords.tup());
+
+        if self.schedule == Schedule::Scanline {
+            for (_, points) in &mut tiles {
+                scanline_sort(points, self.scan_right);
+            }
+            self.scan_right = !self.scan_right;
+        }
+
+        for (parity_x, parity_y) in PASSES {
+            let pass: Vec<Tile> = tiles
+                .iter_mut()
+                .filter(|(coords, _)| {
+                    let (x, y) = coords.tup();
+                    x.rem_euclid(2) == parity_x && y.rem_euclid(2) == parity_y
+                })
+                .map(|(coords, points)| {
+                    let (x, y) = coords.tup();
+                    let min = TILE_SIZE * point(x, y);
+                    Tile {
+                        rect: Rect::new(min, min + point(TILE_SIZE, TILE_SIZE)),
+                        points: std::mem::take(points),
+                        seed: self.data.rng.random(),
+                    }
+                })
+                .collect();
+
+            let data = &self.data;
+            let updated: Vec<_> = pass
+                .into_par_iter()
+                .map(|tile| update_tile(data, tile))
+                .collect();
+
+            for (halo, tile_data) in updated {
+                self.apply_tile(halo, tile_data);
+            }
+        }
+
+        let materials = Arc::clone(&self.data.materials);
+        self.data.conduct(&materials);
+    }
+
+    /// Writes a tile updated by `update_tile` back into the world.
+    fn apply_tile(&mut self, halo: Rect, tile_data: CellData<DenseStore>) {
+        for p in halo.points() {
+            if let Location::Inside(p) = self.data.locate(p) {
+                self.data.store.set_cell(p, tile_data.store.cell_at(p));
+            }
+        }
+
+        for p in tile_data.next_updates {
+            self.data.awaken(p);
+        }
+        self.data.thermal.extend(tile_data.thermal);
+
+        if let (Some(events), Some(tile_events)) = (&mut self.data.events, tile_data.events) {
+            events.extend(tile_events);
+        }
+    }
+}
+
+/// Updates the awake points of `tile` in a copy of it and its one cell border, returning the
+/// border rectangle and the updated copy.
+fn update_tile<S: CellStore>(data: &CellData<S>, tile: Tile) -> (Rect, CellData<DenseStore>) {
+    let halo = Rect::new(tile.rect.min() - point(1, 1), tile.rect.max() + point(1, 1));
+    let mut store = DenseStore::new(halo);
+    for p in halo.points() {
+        if let Location::Inside(p) = data.locate(p) {
+            store.set_cell(p, data.store.cell_at(p));
+        }
+    }
+
+    let mut tile_data = CellData::new(store, tile.seed, Arc::clone(&data.materials));
+    tile_data.bounds = data.bounds;
+    tile_data.reactions = Arc::clone(&data.reactions);
+    tile_data.events = data.events.as_ref().map(|_| Vec::new());
+
+    let mut skip = FxHashSet::default();
+    let mut work = Vec::new();
+
+    for p in tile.points {
+        update_cell(
+            &mut tile_data,
+            &data.materials,
+            &data.reactions,
+            &mut skip,
+            &mut work,
+            p,
+            Some(tile.rect),
+        );
+    }
+
+    (halo, tile_data)
+}
@@ -0,0 +1,288 @@
+//! The binary world format used by `Cells::save` and `Cells::load`.
+//!
+//! All numbers are little endian. A file is laid out as
+//! - `MAGIC`, then the format version as a `u16`
+//! - seed and tick count as `u64`s
+//! - a `u8` that is 1 if bounds follow, then min x, min y, max x, max y as `i32`s and the
+//!   boundary as a `u8`
+//! - min x, min y as `i32`s and width, height as `u32`s of the box holding every non-air cell
+//! - runs of cells covering that box column by column from the bottom left, each a `u32` length
+//!   followed by the `u8` save code of a kind from the `define_materials!` table
+//!
+//! Temperatures and heat sources are not saved, a loaded world starts at `AMBIENT_TEMPERATURE`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{self, Read, Write},
+};
+
+use super::{Boundary, CellKind, Cells, cell};
+use crate::{
+    point::{Rect, point},
+    store::CellStore,
+};
+
+const MAGIC: [u8; 4] = *b"PSND";
+
+/// Newest version this build can write and read.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Why a world could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The data does not start with the world file magic.
+    NotAWorld,
+    /// The data was written by a newer version of the format.
+    UnsupportedVersion(u16),
+    /// The data ended before the world did.
+    Truncated,
+    /// The data is a world file but something in it makes no sense.
+    Malformed(&'static str),
+    Io(io::Error),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NotAWorld => write!(f, "not a world file"),
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "world format version {version} is newer than the supported version {FORMAT_VERSION}"
+            ),
+            LoadError::Truncated => write!(f, "world file ends unexpectedly"),
+            LoadError::Malformed(reason) => write!(f, "malformed world file: {reason}"),
+            LoadError::Io(error) => write!(f, "could not read world: {error}"),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => LoadError::Truncated,
+            _ => LoadError::Io(error),
+        }
+    }
+}
+
+fn boundary_code(boundary: Boundary) -> u8 {
+    match boundary {
+        Boundary::Wall => 0,
+        Boundary::Void => 1,
+        Boundary::Wrap => 2,
+    }
+}
+
+fn code_boundary(code: u8) -> Option<Boundary> {
+    Some(match code {
+        0 => Boundary::Wall,
+        1 => Boundary::Void,
+        2 => Boundary::Wrap,
+        _ => return None,
+    })
+}
+
+impl<S: CellStore> Cells<S> {
+    /// Writes the cells, seed, tick count and bounds of the world to `writer`.
+    ///
+    /// Which cells are awake and the exact state of the randomness are not saved, a loaded world
+    /// restarts its randomness from the seed with every cell awake.
+    ///
+    /// Worlds holding cells of registered kinds cannot be saved.
+    pub fn save(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut cells: Vec<_> = self
+            .data
+            .store
+            .iter()
+            .map(|(point, cell)| (point.tup(), cell.kind))
+            .collect();
+        cells.sort_unstable_by_key(|&(position, _)| position);
+
+        if cells.iter().any(|&(_, kind)| kind.save_code().is_none()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cells of registered kinds cannot be saved",
+            ));
+        }
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&self.tick.to_le_bytes())?;
+
+        match self.data.bounds {
+            Some((rect, boundary)) => {
+                writer.write_all(&[1])?;
+                for value in [rect.min().tup(), rect.max().tup()]
+                    .into_iter()
+                    .flat_map(|(x, y)| [x, y])
+                {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+                writer.write_all(&[boundary_code(boundary)])?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+
+        let min_x = cells.iter().map(|((x, _), _)| *x).min().unwrap_or(0);
+        let max_x = cells.iter().map(|((x, _), _)| *x + 1).max().unwrap_or(0);
+        let min_y = cells.iter().map(|((_, y), _)| *y).min().unwrap_or(0);
+        let max_y = cells.iter().map(|((_, y), _)| *y + 1).max().unwrap_or(0);
+        let width = max_x.abs_diff(min_x);
+        let height = max_y.abs_diff(min_y);
+
+        writer.write_all(&min_x.to_le_bytes())?;
+        writer.write_all(&min_y.to_le_bytes())?;
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+
+        let mut runs = RunWriter::new(writer);
+        let mut next = 0;
+
+        for ((x, y), kind) in cells {
+            let index = x.abs_diff(min_x) as u64 * height as u64 + y.abs_diff(min_y) as u64;
+            runs.push(CellKind::Air, index - next)?;
+            runs.push(kind, 1)?;
+            next = index + 1;
+        }
+
+        runs.push(CellKind::Air, width as u64 * height as u64 - next)?;
+        runs.finish()
+    }
+}
+
+impl Cells {
+    /// Reads a world written by `Cells::save`.
+    pub fn load(reader: &mut impl Read) -> Result<Cells, LoadError> {
+        let magic: [u8; 4] = read_bytes(reader)?;
+        if magic != MAGIC {
+            return Err(LoadError::NotAWorld);
+        }
+
+        let version = u16::from_le_bytes(read_bytes(reader)?);
+        if version > FORMAT_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+
+        let seed = u64::from_le_bytes(read_bytes(reader)?);
+        let tick = u64::from_le_bytes(read_bytes(reader)?);
+
+        let mut cells = Cells::with_seed(seed);
+        cells.tick = tick;
+
+        match read_bytes::<1>(reader)? {
+            [0] => (),
+            [1] => {
+                let min = point(read_i32(reader)?, read_i32(reader)?);
+                let max = point(read_i32(reader)?, read_i32(reader)?);
+                let [code] = read_bytes(reader)?;
+                let boundary =
+                    code_boundary(code).ok_or(LoadError::Malformed("unknown boundary"))?;
+                let rect = Rect::new(min, max);
+                if rect.is_empty() {
+                    return Err(LoadError::Malformed("empty bounds"));
+                }
+                cells = cells.with_bounds(rect, boundary);
+            }
+            _ => return Err(LoadError::Malformed("bad bounds flag")),
+        }
+
+        let min_x = read_i32(reader)?;
+        let min_y = read_i32(reader)?;
+        let width = u32::from_le_bytes(read_bytes(reader)?);
+        let height = u32::from_le_bytes(read_bytes(reader)?);
+
+        let total = width as u64 * height as u64;
+        let mut index = 0;
+
+        while index < total {
+            let length = u32::from_le_bytes(read_bytes(reader)?) as u64;
+            let [code] = read_bytes(reader)?;
+            let kind =
+                CellKind::from_save_code(code).ok_or(LoadError::Malformed("unknown cell kind"))?;
+
+            if length == 0 || index + length > total {
+                return Err(LoadError::Malformed("cell run does not fit the world"));
+            }
+
+            if kind != CellKind::Air {
+                for i in index..index + length {
+                    let x = min_x as i64 + (i / height as u64) as i64;
+                    let y = min_y as i64 + (i % height as u64) as i64;
+                    cells.set_cell(point(x as i32, y as i32), cell(kind));
+                }
+            }
+
+            index += length;
+        }
+
+        Ok(cells)
+    }
+}
+
+/// Merges consecutive cells of the same kind into runs as they are written.
+struct RunWriter<'a, W> {
+    writer: &'a mut W,
+    kind: CellKind,
+    length: u64,
+}
+
+impl<'a, W: Write> RunWriter<'a, W> {
+    fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            kind: CellKind::Air,
+            length: 0,
+        }
+    }
+
+    fn push(&mut self, kind: CellKind, length: u64) -> io::Result<()> {
+        if length == 0 {
+            return Ok(());
+        }
+
+        if kind != self.kind {
+            self.finish()?;
+            self.kind = kind;
+        }
+
+        self.length += length;
+        Ok(())
+    }
+
+    /// Writes out the current run, split into pieces that fit a `u32` length.
+    fn finish(&mut self) -> io::Result<()> {
+        while self.length > 0 {
+            let piece = self.length.min(u32::MAX as u64);
+            self.writer.write_all(&(piece as u32).to_le_bytes())?;
+            let code = self
+                .kind
+                .save_code()
+                .expect("registered kinds are refused before writing");
+            self.writer.write_all(&[code])?;
+            self.length -= piece;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], LoadError> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_i32(reader: &mut impl Read) -> Result<i32, LoadError> {
+    Ok(i32::from_le_bytes(read_bytes(reader)?))
+}
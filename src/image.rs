@@ -0,0 +1,223 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{Read, Write},
+};
+
+use crate::{
+    cells::{CellKind, Cells, cell},
+    material::Materials,
+    point::{Point, Rect, point},
+};
+
+/// Which colour stands for which `CellKind` in an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    entries: Vec<([u8; 4], CellKind)>,
+}
+
+impl Default for Palette {
+    /// Every built-in kind drawn in the colour of its material.
+    fn default() -> Self {
+        let materials = Materials::default();
+        Palette::new(CellKind::ALL.map(|kind| (materials.colour(kind), kind)))
+    }
+}
+
+impl Palette {
+    pub fn new(entries: impl IntoIterator<Item = ([u8; 4], CellKind)>) -> Palette {
+        Palette {
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    /// Adds `colour` for `kind`, taking over from any earlier entry for the same colour.
+    pub fn with(mut self, colour: [u8; 4], kind: CellKind) -> Palette {
+        self.entries.retain(|&(existing, _)| existing != colour);
+        self.entries.push((colour, kind));
+        self
+    }
+
+    pub fn kind_of(&self, colour: [u8; 4]) -> Option<CellKind> {
+        self.entries
+            .iter()
+            .find(|&&(entry, _)| entry == colour)
+            .map(|&(_, kind)| kind)
+    }
+
+    /// The kind whose colour is closest to `colour`, `None` only for an empty palette.
+    pub fn nearest(&self, colour: [u8; 4]) -> Option<CellKind> {
+        self.entries
+            .iter()
+            .min_by_key(|(entry, _)| {
+                entry
+                    .iter()
+                    .zip(colour)
+                    .map(|(&a, b)| (a as i32 - b as i32).pow(2))
+                    .sum::<i32>()
+            })
+            .map(|&(_, kind)| kind)
+    }
+
+    /// The first colour given for `kind`, if any.
+    pub fn colour_of(&self, kind: CellKind) -> Option<[u8; 4]> {
+        self.entries
+            .iter()
+            .find(|&&(_, entry)| entry == kind)
+            .map(|&(colour, _)| colour)
+    }
+}
+
+/// What to do with a pixel whose colour is not in the palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownColour {
+    /// Use the kind with the closest colour.
+    #[default]
+    Nearest,
+    /// Fail with `ImageError::UnknownColour`.
+    Error,
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Decode(png::DecodingError),
+    Encode(png::EncodingError),
+    /// A pixel, counted from the top left of the image, has a colour the palette does not know.
+    UnknownColour {
+        colour: [u8; 4],
+        x: u32,
+        y: u32,
+    },
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Decode(error) => write!(f, "could not read image: {error}"),
+            ImageError::Encode(error) => write!(f, "could not write image: {error}"),
+            ImageError::UnknownColour { colour, x, y } => {
+                write!(
+                    f,
+                    "pixel ({x}, {y}) has colour {colour:?} which is not in the palette"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::Decode(error) => Some(error),
+            ImageError::Encode(error) => Some(error),
+            ImageError::UnknownColour { .. } => None,
+        }
+    }
+}
+
+impl From<png::DecodingError> for ImageError {
+    fn from(error: png::DecodingError) -> Self {
+        ImageError::Decode(error)
+    }
+}
+
+impl From<png::EncodingError> for ImageError {
+    fn from(error: png::EncodingError) -> Self {
+        ImageError::Encode(error)
+    }
+}
+
+impl Cells {
+    /// Builds a world from a PNG with its bottom left pixel at `origin`, one cell per pixel.
+    ///
+    /// Fully transparent pixels are always air. The seed is 0 rather than random, call `reseed`
+    /// before stepping for a different run of the same scene.
+    pub fn from_image(
+        reader: impl Read,
+        origin: Point,
+        palette: &Palette,
+        unknown: UnknownColour,
+    ) -> Result<Cells, ImageError> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let bytes = &buffer[..info.buffer_size()];
+
+        let mut cells = Cells::with_seed(0);
+
+        for (i, pixel) in bytes.chunks_exact(info.color_type.samples()).enumerate() {
+            let x = i as u32 % info.width;
+            let y = i as u32 / info.width;
+
+            let colour = match *pixel {
+                [r, g, b, a] => [r, g, b, a],
+                [r, g, b] => [r, g, b, 255],
+                [l, a] => [l, l, l, a],
+                [l] => [l, l, l, 255],
+                _ => unreachable!("normalised PNGs have one to four samples per pixel"),
+            };
+
+            if colour[3] == 0 {
+                continue;
+            }
+
+            let kind = match (palette.kind_of(colour), unknown) {
+                (Some(kind), _) => kind,
+                (None, UnknownColour::Nearest) => palette
+                    .nearest(colour)
+                    .ok_or(ImageError::UnknownColour { colour, x, y })?,
+                (None, UnknownColour::Error) => {
+                    return Err(ImageError::UnknownColour { colour, x, y });
+                }
+            };
+
+            let offset = point(x as i32, (info.height - 1 - y) as i32);
+            cells.set_cell(origin + offset, cell(kind));
+        }
+
+        Ok(cells)
+    }
+
+    /// Writes `rect` of the world as a PNG, one pixel per cell.
+    ///
+    /// Kinds missing from the palette are drawn in the colour of their material.
+    pub fn to_image(
+        &self,
+        rect: Rect,
+        palette: &Palette,
+        writer: impl Write,
+    ) -> Result<(), ImageError> {
+        let (min_x, min_y) = rect.min().tup();
+        let (max_x, max_y) = rect.max().tup();
+
+        let mut bytes =
+            Vec::with_capacity(rect.width().max(0) as usize * rect.height().max(0) as usize * 4);
+
+        for y in (min_y..max_y).rev() {
+            for x in min_x..max_x {
+                let kind = self.cell_at(point(x, y)).kind();
+                let colour = palette
+                    .colour_of(kind)
+                    .unwrap_or_else(|| self.materials().colour(kind));
+                bytes.extend_from_slice(&colour);
+            }
+        }
+
+        let mut encoder = png::Encoder::new(
+            writer,
+            rect.width().max(0) as u32,
+            rect.height().max(0) as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&bytes)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
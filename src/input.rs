@@ -30,6 +30,12 @@ macro_rules! create_input_actions {
     };
 }
 
+impl Default for InputManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InputManager {
     captured: bool,
@@ -76,7 +82,7 @@ impl InputManager {
             return true;
         }
 
-        return false;
+        false
     }
 
     pub fn down(&self, action: Ina) -> bool {
@@ -1,3 +1,9 @@
+pub mod ascii;
 pub mod cells;
+pub mod image;
 pub mod input;
+pub mod material;
 pub mod point;
+pub mod reaction;
+pub mod stamp;
+pub mod store;
@@ -0,0 +1,324 @@
+use std::{
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use rand::rngs::SmallRng;
+
+use crate::{
+    cells::{Cell, CellKind},
+    point::Point,
+};
+
+mod config;
+
+pub use config::{ConfigError, ConfigMaterial, ConfigTransition, Movement};
+
+/// How a kind of cell looks and behaves.
+///
+/// Register one with `Cells::register` to get a `CellKind` for it.
+pub trait Material: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn colour(&self) -> [u8; 4];
+
+    /// Denser cells sink through lighter ones.
+    fn density(&self) -> i32;
+
+    /// How much heat it takes to warm the cell by one degree.
+    fn heat_capacity(&self) -> f32 {
+        1.0
+    }
+
+    /// How readily heat flows between the cell and its neighbours, from 0 for an insulator. Heat
+    /// flows at the rate of the worse conductor of the two.
+    fn conductivity(&self) -> f32 {
+        0.05
+    }
+
+    /// Neighbours, relative to the cell, that are updated before it moves, so it can move into
+    /// the space they leave.
+    fn update_first(&self) -> &[Point] {
+        &[]
+    }
+
+    /// The changes of state the cell goes through when it gets too hot or too cold, the first
+    /// whose threshold the cell is past applies.
+    fn transitions(&self) -> &[Transition] {
+        &[]
+    }
+
+    /// Locked cells, like bedrock, are refused by `Cells::try_set`, `try_update` and `try_swap`.
+    fn locked(&self) -> bool {
+        false
+    }
+
+    /// Moves the cell at `point`. A material that does nothing here never moves by itself.
+    fn update(&self, world: &mut UpdateContext<'_>, point: Point) {
+        let _ = (world, point);
+    }
+}
+
+/// A change of state, like water freezing into ice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub threshold: Threshold,
+    pub into: CellKind,
+    /// Updates the cell must spend past the threshold before it changes, standing in for the
+    /// latent heat of the change.
+    pub delay: u16,
+}
+
+/// The side of a temperature a cell must be on for a `Transition`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    Below(f32),
+    Above(f32),
+}
+
+impl Transition {
+    pub const fn below(temperature: f32, into: CellKind, delay: u16) -> Transition {
+        Transition {
+            threshold: Threshold::Below(temperature),
+            into,
+            delay,
+        }
+    }
+
+    pub const fn above(temperature: f32, into: CellKind, delay: u16) -> Transition {
+        Transition {
+            threshold: Threshold::Above(temperature),
+            into,
+            delay,
+        }
+    }
+
+    /// Whether a cell at `temperature` is past the threshold.
+    pub fn passed(&self, temperature: f32) -> bool {
+        match self.threshold {
+            Threshold::Below(threshold) => temperature < threshold,
+            Threshold::Above(threshold) => temperature > threshold,
+        }
+    }
+}
+
+/// What a `Material` can see and do while it updates one of its cells.
+pub struct UpdateContext<'a> {
+    world: &'a mut dyn World,
+}
+
+/// The parts of a world that materials get through `UpdateContext`.
+pub(crate) trait World {
+    fn cell_at(&self, point: Point) -> Cell;
+    fn set_cell(&mut self, point: Point, cell: Cell);
+    fn try_swap(&mut self, from: Point, to: Point) -> bool;
+    fn rest(&mut self, point: Point, offsets: &[Point]) -> bool;
+    fn awaken(&mut self, point: Point);
+    fn rng(&mut self) -> &mut SmallRng;
+}
+
+impl<'a> UpdateContext<'a> {
+    pub(crate) fn new(world: &'a mut dyn World) -> Self {
+        Self { world }
+    }
+
+    pub fn cell_at(&self, point: Point) -> Cell {
+        self.world.cell_at(point)
+    }
+
+    /// Writes `cell` at `point` and wakes everything around it.
+    pub fn set_cell(&mut self, point: Point, cell: Cell) {
+        self.world.set_cell(point, cell);
+    }
+
+    /// Tries to move the cell at `from` to `to`, it only goes if it is denser than what is there
+    /// and with a chance that grows with the difference.
+    pub fn try_swap(&mut self, from: Point, to: Point) -> bool {
+        self.world.try_swap(from, to)
+    }
+
+    /// Tries `try_swap` with each offset from `from` in turn, stopping at the first that works.
+    pub fn multi_try_swap(&mut self, from: Point, offsets: &[Point]) -> bool {
+        offsets
+            .iter()
+            .any(|&offset| self.world.try_swap(from, from + offset))
+    }
+
+    /// Lets the cell at `point` fall asleep unless it could still move to one of `offsets`.
+    pub fn rest(&mut self, point: Point, offsets: &[Point]) -> bool {
+        self.world.rest(point, offsets)
+    }
+
+    /// Schedules `point` for the next update.
+    pub fn awaken(&mut self, point: Point) {
+        self.world.awaken(point);
+    }
+
+    /// The world's random numbers, use these rather than your own so seeds stay reproducible.
+    pub fn rng(&mut self) -> &mut SmallRng {
+        self.world.rng()
+    }
+}
+
+/// The materials a world knows about, the built-in kinds first and then any registered ones.
+#[derive(Clone)]
+pub struct Materials {
+    entries: Vec<Arc<dyn Material>>,
+    /// Copied out of `entries` as these are looked up for every attempted move, drawn pixel and
+    /// exchange of heat.
+    densities: Vec<i32>,
+    colours: Vec<[u8; 4]>,
+    heat_capacities: Vec<f32>,
+    conductivities: Vec<f32>,
+}
+
+impl Default for Materials {
+    /// Every built-in kind.
+    fn default() -> Self {
+        let mut materials = Materials {
+            entries: Vec::new(),
+            densities: Vec::new(),
+            colours: Vec::new(),
+            heat_capacities: Vec::new(),
+            conductivities: Vec::new(),
+        };
+
+        for kind in CellKind::ALL {
+            materials.push(Arc::new(BuiltIn(kind)));
+        }
+
+        materials
+    }
+}
+
+impl Debug for Materials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|material| material.name()))
+            .finish()
+    }
+}
+
+impl Materials {
+    /// Adds `material`, returning the kind that stands for it.
+    pub fn register(&mut self, material: impl Material + 'static) -> CellKind {
+        let id = self.entries.len() - CellKind::ALL.len();
+        let id = u16::try_from(id).expect("too many materials registered");
+        self.push(Arc::new(material));
+        CellKind::Custom(id)
+    }
+
+    /// Swaps the material behind `kind` for `material`, returning `false` if `kind` is not known.
+    pub fn replace(&mut self, kind: CellKind, material: impl Material + 'static) -> bool {
+        let index = kind.index();
+        if index >= self.entries.len() {
+            return false;
+        }
+
+        self.densities[index] = material.density();
+        self.colours[index] = material.colour();
+        self.heat_capacities[index] = material.heat_capacity();
+        self.conductivities[index] = material.conductivity();
+        self.entries[index] = Arc::new(material);
+        true
+    }
+
+    /// The material for `kind`. Kinds registered with another world are treated as air.
+    pub fn get(&self, kind: CellKind) -> &dyn Material {
+        match self.entries.get(kind.index()) {
+            Some(material) => material.as_ref(),
+            None => self.entries[CellKind::Air.index()].as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn colour(&self, kind: CellKind) -> [u8; 4] {
+        match self.colours.get(kind.index()) {
+            Some(&colour) => colour,
+            None => self.colours[CellKind::Air.index()],
+        }
+    }
+
+    #[inline]
+    pub fn density(&self, kind: CellKind) -> i32 {
+        match self.densities.get(kind.index()) {
+            Some(&density) => density,
+            None => self.densities[CellKind::Air.index()],
+        }
+    }
+
+    #[inline]
+    pub fn heat_capacity(&self, kind: CellKind) -> f32 {
+        match self.heat_capacities.get(kind.index()) {
+            Some(&heat_capacity) => heat_capacity,
+            None => self.heat_capacities[CellKind::Air.index()],
+        }
+    }
+
+    #[inline]
+    pub fn conductivity(&self, kind: CellKind) -> f32 {
+        match self.conductivities.get(kind.index()) {
+            Some(&conductivity) => conductivity,
+            None => self.conductivities[CellKind::Air.index()],
+        }
+    }
+
+    /// Every kind, built-in and registered.
+    pub fn kinds(&self) -> impl Iterator<Item = CellKind> + '_ {
+        (0..self.entries.len()).map(CellKind::from_index)
+    }
+
+    /// The kind whose material has `name`, if any.
+    pub fn find(&self, name: &str) -> Option<CellKind> {
+        self.kinds().find(|&kind| self.get(kind).name() == name)
+    }
+
+    fn push(&mut self, material: Arc<dyn Material>) {
+        self.densities.push(material.density());
+        self.colours.push(material.colour());
+        self.heat_capacities.push(material.heat_capacity());
+        self.conductivities.push(material.conductivity());
+        self.entries.push(material);
+    }
+}
+
+/// A built-in kind, registered like any other material.
+struct BuiltIn(CellKind);
+
+impl Material for BuiltIn {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn colour(&self) -> [u8; 4] {
+        crate::cells::colour(self.0)
+    }
+
+    fn density(&self) -> i32 {
+        crate::cells::density(self.0)
+    }
+
+    fn heat_capacity(&self) -> f32 {
+        crate::cells::heat_capacity(self.0)
+    }
+
+    fn conductivity(&self) -> f32 {
+        crate::cells::conductivity(self.0)
+    }
+
+    fn update_first(&self) -> &[Point] {
+        crate::cells::update_first(self.0)
+    }
+
+    fn transitions(&self) -> &[Transition] {
+        crate::cells::transitions(self.0)
+    }
+
+    fn locked(&self) -> bool {
+        crate::cells::locked(self.0)
+    }
+
+    fn update(&self, world: &mut UpdateContext<'_>, point: Point) {
+        crate::cells::move_cell(world, point, self.0);
+    }
+}
@@ -0,0 +1,394 @@
+//! Materials described in a TOML file, one `[[material]]` table per material:
+//!
+//! ```toml
+//! [[material]]
+//! name = "Red Sand"
+//! colour = [200, 60, 40, 255]
+//! density = 30
+//! movement = "fall_tumble"
+//! heat_capacity = 1.0
+//! conductivity = 0.05
+//! ```
+//!
+//! `movement` is one of `static`, `fall_tumble`, `fall_slide` and `rise_slide`, named after the
+//! offset tables in `point`. `heat_capacity` and `conductivity` may be left out for the defaults
+//! of `Material`, and `locked = true` makes the checked edits refuse the material.
+//!
+//! A material named after one that is already known, like a built-in kind, retunes it. It leaves
+//! out `movement` and keeps moving the way it did, only its colour, density, heat capacity,
+//! conductivity and transitions come from the file. It stays locked or unlocked unless the file
+//! gives `locked`.
+//!
+//! Changes of state follow their material as `[[material.transition]]` tables, tried in order:
+//!
+//! ```toml
+//! [[material.transition]]
+//! below = 0.0
+//! into = "Ice"
+//! delay = 20
+//! ```
+//!
+//! Each has exactly one of `below` and `above`, and `into` names a material that is already known
+//! or is in the same file.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    sync::Arc,
+};
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{Material, Materials, Threshold, Transition, UpdateContext};
+use crate::{
+    cells::CellKind,
+    point::{
+        DOWN, FALL_SLIDE_LEFT, FALL_SLIDE_RIGHT, FALL_TUMBLE_LEFT, FALL_TUMBLE_RIGHT, LEFT, Point,
+        RIGHT, RISE_SLIDE_LEFT, RISE_SLIDE_RIGHT, UP,
+    },
+};
+
+/// How a material from a config file moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Movement {
+    /// Never moves by itself.
+    Static,
+    /// Falls straight or diagonally down, like sand.
+    FallTumble,
+    /// Falls, or spreads sideways when it cannot, like water.
+    FallSlide,
+    /// Rises, or spreads sideways when it cannot, like a gas.
+    RiseSlide,
+}
+
+/// A material read from a config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigMaterial {
+    pub name: String,
+    pub colour: [u8; 4],
+    pub density: i32,
+    /// Needed for new materials, and left out for ones that are being retuned.
+    #[serde(default)]
+    pub movement: Option<Movement>,
+    #[serde(default = "default_heat_capacity")]
+    pub heat_capacity: f32,
+    #[serde(default = "default_conductivity")]
+    pub conductivity: f32,
+    /// Left out for unlocked new materials, and for retuned ones that stay as they were.
+    #[serde(default)]
+    pub locked: Option<bool>,
+    #[serde(default, rename = "transition")]
+    pub transitions: Vec<ConfigTransition>,
+    /// `transitions` with their materials looked up, filled in by `Materials::load`.
+    #[serde(skip)]
+    resolved: Vec<Transition>,
+}
+
+/// A transition read from a config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigTransition {
+    pub below: Option<f32>,
+    pub above: Option<f32>,
+    /// Name of the material the cell becomes.
+    pub into: String,
+    #[serde(default)]
+    pub delay: u16,
+}
+
+fn default_heat_capacity() -> f32 {
+    1.0
+}
+
+fn default_conductivity() -> f32 {
+    0.05
+}
+
+impl Material for ConfigMaterial {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn colour(&self) -> [u8; 4] {
+        self.colour
+    }
+
+    fn density(&self) -> i32 {
+        self.density
+    }
+
+    fn heat_capacity(&self) -> f32 {
+        self.heat_capacity
+    }
+
+    fn conductivity(&self) -> f32 {
+        self.conductivity
+    }
+
+    fn transitions(&self) -> &[Transition] {
+        &self.resolved
+    }
+
+    fn locked(&self) -> bool {
+        self.locked.unwrap_or(false)
+    }
+
+    fn update_first(&self) -> &[Point] {
+        match self.movement {
+            None | Some(Movement::Static) => &[],
+            Some(Movement::FallTumble) => &[DOWN],
+            Some(Movement::FallSlide) => &[DOWN, RIGHT, LEFT],
+            Some(Movement::RiseSlide) => &[UP, RIGHT, LEFT],
+        }
+    }
+
+    fn update(&self, world: &mut UpdateContext<'_>, point: Point) {
+        let Some(movement) = self.movement else {
+            return;
+        };
+        let right = world.rng().random_bool(0.5);
+
+        let targets: &[Point] = match (movement, right) {
+            (Movement::Static, _) => return,
+            (Movement::FallTumble, true) => &FALL_TUMBLE_RIGHT,
+            (Movement::FallTumble, false) => &FALL_TUMBLE_LEFT,
+            (Movement::FallSlide, true) => &FALL_SLIDE_RIGHT,
+            (Movement::FallSlide, false) => &FALL_SLIDE_LEFT,
+            (Movement::RiseSlide, true) => &RISE_SLIDE_RIGHT,
+            (Movement::RiseSlide, false) => &RISE_SLIDE_LEFT,
+        };
+
+        let _ = world.multi_try_swap(point, targets) || world.rest(point, targets);
+    }
+}
+
+/// A known material retuned by a config file, which still moves the way it did.
+struct Retuned {
+    base: Arc<dyn Material>,
+    config: ConfigMaterial,
+}
+
+impl Material for Retuned {
+    fn name(&self) -> &str {
+        self.base.name()
+    }
+
+    fn colour(&self) -> [u8; 4] {
+        self.config.colour
+    }
+
+    fn density(&self) -> i32 {
+        self.config.density
+    }
+
+    fn heat_capacity(&self) -> f32 {
+        self.config.heat_capacity
+    }
+
+    fn conductivity(&self) -> f32 {
+        self.config.conductivity
+    }
+
+    fn transitions(&self) -> &[Transition] {
+        &self.config.resolved
+    }
+
+    fn locked(&self) -> bool {
+        self.config.locked.unwrap_or_else(|| self.base.locked())
+    }
+
+    fn update_first(&self) -> &[Point] {
+        self.base.update_first()
+    }
+
+    fn update(&self, world: &mut UpdateContext<'_>, point: Point) {
+        self.base.update(world, point);
+    }
+}
+
+/// Why a material config could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The file is not valid TOML.
+    Syntax(String),
+    /// A material is missing a field or has a bad value. `entry` is its name, or its position in
+    /// the file counting from one if it has no name.
+    Invalid { entry: String, reason: String },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Syntax(reason) => write!(f, "material config is not valid TOML: {reason}"),
+            ConfigError::Invalid { entry, reason } => write!(f, "material {entry}: {reason}"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Reads every material in `text`, checking each one.
+fn parse(text: &str) -> Result<Vec<ConfigMaterial>, ConfigError> {
+    let mut table: toml::Table =
+        toml::from_str(text).map_err(|error| ConfigError::Syntax(error.to_string()))?;
+
+    let entries = match table.remove("material") {
+        Some(toml::Value::Array(entries)) => entries,
+        Some(_) => {
+            return Err(ConfigError::Syntax(
+                "`material` must be an array of tables".into(),
+            ));
+        }
+        None => Vec::new(),
+    };
+
+    if let Some(key) = table.keys().next() {
+        return Err(ConfigError::Syntax(format!("unknown key `{key}`")));
+    }
+
+    let mut materials: Vec<ConfigMaterial> = Vec::new();
+
+    for (i, entry) in entries.into_iter().enumerate() {
+        let name = match entry.get("name").and_then(toml::Value::as_str) {
+            Some(name) => format!("{name:?}"),
+            None => format!("#{}", i + 1),
+        };
+        let invalid = |reason: String| ConfigError::Invalid {
+            entry: name.clone(),
+            reason,
+        };
+
+        let material: ConfigMaterial = entry
+            .try_into()
+            .map_err(|error: toml::de::Error| invalid(error.message().to_owned()))?;
+
+        if material.name.trim().is_empty() {
+            return Err(invalid("name must not be empty".into()));
+        }
+        if material.name == CellKind::Air.name() {
+            return Err(invalid("air cannot be redefined".into()));
+        }
+        if material.density < 0 {
+            return Err(invalid("density must not be negative".into()));
+        }
+        if !(material.heat_capacity.is_finite() && material.heat_capacity > 0.0) {
+            return Err(invalid("heat_capacity must be positive".into()));
+        }
+        if !(material.conductivity.is_finite() && material.conductivity >= 0.0) {
+            return Err(invalid("conductivity must not be negative".into()));
+        }
+        if material
+            .transitions
+            .iter()
+            .any(|transition| transition.below.is_some() == transition.above.is_some())
+        {
+            return Err(invalid(
+                "each transition needs exactly one of `below` and `above`".into(),
+            ));
+        }
+        if materials.iter().any(|other| other.name == material.name) {
+            return Err(invalid("is defined more than once".into()));
+        }
+
+        materials.push(material);
+    }
+
+    Ok(materials)
+}
+
+impl Materials {
+    /// Adds every material in the TOML `text`, returning their kinds in the order they were
+    /// written. A material with the name of one that is already known retunes it, keeping the
+    /// way it moves, so the file can adjust the built-in kinds. Air is always empty space and
+    /// cannot be redefined.
+    ///
+    /// Nothing is added unless the whole file is valid.
+    pub fn load(&mut self, text: &str) -> Result<Vec<CellKind>, ConfigError> {
+        let mut materials = parse(text)?;
+
+        // The kinds the materials will get, so transitions can name materials in the same file.
+        let mut next_id = self.entries.len() - CellKind::ALL.len();
+        let kinds: Vec<CellKind> = materials
+            .iter()
+            .map(|material| {
+                self.find(&material.name).unwrap_or_else(|| {
+                    let id = u16::try_from(next_id).expect("too many materials registered");
+                    next_id += 1;
+                    CellKind::Custom(id)
+                })
+            })
+            .collect();
+
+        let kind_of = |name: &str| {
+            materials
+                .iter()
+                .position(|material| material.name == name)
+                .map(|i| kinds[i])
+                .or_else(|| self.find(name))
+        };
+
+        let mut resolved = Vec::with_capacity(materials.len());
+        for material in &materials {
+            let invalid = |reason: &str| ConfigError::Invalid {
+                entry: format!("{:?}", material.name),
+                reason: reason.to_owned(),
+            };
+            match (self.find(&material.name), material.movement) {
+                (Some(_), Some(_)) => {
+                    return Err(invalid("a known material keeps its movement"));
+                }
+                (None, None) => return Err(invalid("missing field `movement`")),
+                _ => (),
+            }
+
+            let transitions = material
+                .transitions
+                .iter()
+                .map(|transition| {
+                    let into = kind_of(&transition.into).ok_or_else(|| ConfigError::Invalid {
+                        entry: format!("{:?}", material.name),
+                        reason: format!("transition into unknown material {:?}", transition.into),
+                    })?;
+
+                    let threshold = match (transition.below, transition.above) {
+                        (Some(below), None) => Threshold::Below(below),
+                        (None, Some(above)) => Threshold::Above(above),
+                        _ => unreachable!("checked by `parse`"),
+                    };
+
+                    Ok(Transition {
+                        threshold,
+                        into,
+                        delay: transition.delay,
+                    })
+                })
+                .collect::<Result<Vec<_>, ConfigError>>()?;
+            resolved.push(transitions);
+        }
+
+        for (material, transitions) in materials.iter_mut().zip(resolved) {
+            material.resolved = transitions;
+        }
+
+        Ok(materials
+            .into_iter()
+            .map(|material| match self.find(&material.name) {
+                Some(kind) => {
+                    let base = Arc::clone(&self.entries[kind.index()]);
+                    self.replace(
+                        kind,
+                        Retuned {
+                            base,
+                            config: material,
+                        },
+                    );
+                    kind
+                }
+                None => self.register(material),
+            })
+            .collect())
+    }
+}
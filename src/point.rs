@@ -1,4 +1,4 @@
-use std::ops::{Add, Mul};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
 pub const FALL_SLIDE_LEFT: [Point; 5] = [
     point(0, -1),
@@ -24,15 +24,9 @@ pub const RISE_SLIDE_LEFT: [Point; 5] = [
     point(1, 0),
 ];
 
-pub const SLIDE_LEFT: [Point; 2] = [
-    point(-1, 0),
-    point(1, 0),
-];
+pub const SLIDE_LEFT: [Point; 2] = [point(-1, 0), point(1, 0)];
 
-pub const SLIDE_RIGHT: [Point; 2] = [
-    point(-1, 0),
-    point(1, 0),
-];
+pub const SLIDE_RIGHT: [Point; 2] = [point(-1, 0), point(1, 0)];
 
 pub const RISE_SLIDE_RIGHT: [Point; 5] = [
     point(0, 1),
@@ -70,13 +64,16 @@ pub struct Point {
 }
 
 impl Point {
-    pub fn index(self, y_size: usize) -> usize {
-        let (x, y) = self.utup();
-        x * y_size + y
+    /// Index of the point in a grid stored column by column with `y_size` cells per column,
+    /// `None` if the point is not in such a grid.
+    pub fn index(self, y_size: usize) -> Option<usize> {
+        let (x, y) = self.try_utup()?;
+        (y < y_size).then(|| x * y_size + y)
     }
 
-    pub fn utup(self) -> (usize, usize) {
-        (self.x as usize, self.y as usize)
+    /// The coordinates as `usize`s, `None` if either is negative.
+    pub fn try_utup(self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?))
     }
 
     pub fn tup(self) -> (i32, i32) {
@@ -84,6 +81,148 @@ impl Point {
     }
 }
 
+impl From<(i32, i32)> for Point {
+    fn from((x, y): (i32, i32)) -> Self {
+        point(x, y)
+    }
+}
+
+impl From<Point> for (i32, i32) {
+    fn from(point: Point) -> Self {
+        point.tup()
+    }
+}
+
+/// An axis aligned rectangle of points, `min` is inside it while `max` is just outside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    min: Point,
+    max: Point,
+}
+
+impl Rect {
+    pub const fn new(min: Point, max: Point) -> Rect {
+        Rect { min, max }
+    }
+
+    pub fn min(self) -> Point {
+        self.min
+    }
+
+    pub fn max(self) -> Point {
+        self.max
+    }
+
+    pub fn width(self) -> i32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(self) -> i32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn contains(self, point: Point) -> bool {
+        (self.min.x..self.max.x).contains(&point.x) && (self.min.y..self.max.y).contains(&point.y)
+    }
+
+    /// Maps `point` into the rectangle as if its opposite edges were joined together. The
+    /// rectangle must not be empty.
+    pub fn wrap(self, point: Point) -> Point {
+        Point {
+            x: self.min.x + (point.x - self.min.x).rem_euclid(self.width()),
+            y: self.min.y + (point.y - self.min.y).rem_euclid(self.height()),
+        }
+    }
+
+    /// `true` if the rectangle has no points in it.
+    pub fn is_empty(self) -> bool {
+        self.width() <= 0 || self.height() <= 0
+    }
+
+    /// `true` if every point of `other` is also in this rectangle.
+    pub fn contains_rect(self, other: Rect) -> bool {
+        other.is_empty()
+            || (self.min.x <= other.min.x
+                && self.min.y <= other.min.y
+                && other.max.x <= self.max.x
+                && other.max.y <= self.max.y)
+    }
+
+    /// The points in both rectangles, `None` if they do not overlap.
+    pub fn intersection(self, other: Rect) -> Option<Rect> {
+        let rect = Rect {
+            min: point(self.min.x.max(other.min.x), self.min.y.max(other.min.y)),
+            max: point(self.max.x.min(other.max.x), self.max.y.min(other.max.y)),
+        };
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// Every point in the rectangle, column by column from the bottom left.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        let ys = self.min.y..self.max.y;
+        (self.min.x..self.max.x).flat_map(move |x| ys.clone().map(move |y| point(x, y)))
+    }
+}
+
+/// Every point on the straight line from `from` to `to`, both ends included.
+pub fn line(from: Point, to: Point) -> impl Iterator<Item = Point> {
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let step = point((to.x - from.x).signum(), (to.y - from.y).signum());
+
+    let mut next = Some(from);
+    let mut error = dx + dy;
+
+    std::iter::from_fn(move || {
+        let current = next?;
+
+        next = if current == to {
+            None
+        } else {
+            let mut moved = current;
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                moved.x += step.x;
+            }
+            if doubled <= dx {
+                error += dx;
+                moved.y += step.y;
+            }
+            Some(moved)
+        };
+
+        Some(current)
+    })
+}
+
+/// Every point within `radius` of `centre`.
+pub fn circle(centre: Point, radius: i32) -> impl Iterator<Item = Point> {
+    let radius = radius.max(0);
+    let corner = point(radius, radius);
+
+    Rect::new(centre - corner, centre + corner + point(1, 1))
+        .points()
+        .filter(move |&p| in_circle(p - centre, radius))
+}
+
+/// The points of `circle` that are next to a point outside of it.
+pub fn circle_outline(centre: Point, radius: i32) -> impl Iterator<Item = Point> {
+    let radius = radius.max(0);
+
+    circle(centre, radius).filter(move |&p| {
+        [UP, DOWN, LEFT, RIGHT]
+            .into_iter()
+            .any(|offset| !in_circle(p + offset - centre, radius))
+    })
+}
+
+/// Uses `radius * (radius + 1)` rather than `radius²` so circles do not grow a lone point at the
+/// end of each axis.
+fn in_circle(offset: Point, radius: i32) -> bool {
+    offset.x * offset.x + offset.y * offset.y <= radius * (radius + 1)
+}
+
 impl Add for Point {
     type Output = Point;
 
@@ -95,6 +234,37 @@ impl Add for Point {
     }
 }
 
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Self::Output {
+        point(-self.x, -self.y)
+    }
+}
+
 impl Mul<Point> for i32 {
     type Output = Point;
 
@@ -0,0 +1,115 @@
+use rustc_hash::FxHashSet;
+
+use crate::cells::CellKind::{self, *};
+
+/// Two touching cells turning into two others, like water soaking into sand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reaction {
+    /// The kinds that react, in either order.
+    pub reactants: (CellKind, CellKind),
+    /// What the first and second reactant become.
+    pub products: (CellKind, CellKind),
+    /// Chance of reacting each time one of the cells updates next to the other.
+    pub probability: f64,
+}
+
+impl Reaction {
+    /// # Panics
+    /// If `probability` is not between 0 and 1.
+    pub fn new(
+        reactants: (CellKind, CellKind),
+        products: (CellKind, CellKind),
+        probability: f64,
+    ) -> Reaction {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "reaction probability {probability} is not between 0 and 1"
+        );
+
+        Reaction {
+            reactants,
+            products,
+            probability,
+        }
+    }
+
+    /// What a cell of `kind` next to a cell of `other` becomes, and what `other` becomes, if this
+    /// reaction is between them.
+    pub fn products_for(&self, kind: CellKind, other: CellKind) -> Option<(CellKind, CellKind)> {
+        match self.reactants {
+            (first, second) if (first, second) == (kind, other) => Some(self.products),
+            (first, second) if (second, first) == (kind, other) => {
+                Some((self.products.1, self.products.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The reactions a world knows about, none unless some are added.
+#[derive(Debug, Clone, Default)]
+pub struct Reactions {
+    reactions: Vec<Reaction>,
+    /// Every kind in a reaction, so cells of any other kind are passed over without a search.
+    reactants: FxHashSet<CellKind>,
+}
+
+impl Reactions {
+    /// Example reactions between the built-in kinds: water soaks into sand, making wet sand.
+    pub fn built_in() -> Reactions {
+        let mut reactions = Reactions::default();
+        reactions.add(Reaction::new((Water, Sand), (Air, WetSand), 0.05));
+        reactions
+    }
+
+    /// Adds `reaction`, after any that are already known, which are tried first.
+    pub fn add(&mut self, reaction: Reaction) {
+        let (first, second) = reaction.reactants;
+        self.reactants.insert(first);
+        self.reactants.insert(second);
+        self.reactions.push(reaction);
+    }
+
+    /// Removes every reaction between `first` and `second`, returning how many there were.
+    pub fn remove(&mut self, first: CellKind, second: CellKind) -> usize {
+        let before = self.reactions.len();
+        self.reactions
+            .retain(|reaction| reaction.products_for(first, second).is_none());
+
+        self.reactants = self
+            .reactions
+            .iter()
+            .flat_map(|reaction| [reaction.reactants.0, reaction.reactants.1])
+            .collect();
+
+        before - self.reactions.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.reactions.clear();
+        self.reactants.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Reaction> + '_ {
+        self.reactions.iter()
+    }
+
+    /// Whether cells of `kind` take part in any reaction.
+    #[inline]
+    pub fn reacts(&self, kind: CellKind) -> bool {
+        self.reactants.contains(&kind)
+    }
+
+    /// The first reaction between a cell of `kind` and one of `other`, with what each becomes.
+    pub fn find(
+        &self,
+        kind: CellKind,
+        other: CellKind,
+    ) -> Option<(&Reaction, (CellKind, CellKind))> {
+        self.reactions.iter().find_map(|reaction| {
+            reaction
+                .products_for(kind, other)
+                .map(|products| (reaction, products))
+        })
+    }
+}
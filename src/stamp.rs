@@ -0,0 +1,158 @@
+use crate::{
+    cells::{Cell, CellKind, Cells, cell},
+    point::{Point, Rect, point},
+    store::CellStore,
+};
+
+/// A rectangle of cells copied out of a world that can be turned, mirrored and pasted back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stamp {
+    width: i32,
+    height: i32,
+    /// Column by column from the bottom left, the same layout as `Point::index`.
+    cells: Vec<Cell>,
+}
+
+/// How a pasted stamp is combined with what is already in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Blend {
+    /// Every cell of the stamp replaces the world, air included.
+    #[default]
+    Overwrite,
+    /// The stamp is only written where the world is air.
+    IntoAir,
+    /// Air in the stamp leaves the world as it is.
+    SkipAir,
+}
+
+impl Stamp {
+    /// A stamp of only air.
+    pub fn new(width: i32, height: i32) -> Stamp {
+        let (width, height) = (width.max(0), height.max(0));
+
+        Stamp {
+            width,
+            height,
+            cells: vec![cell(CellKind::Air); width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// The cell at `offset` from the bottom left of the stamp, air outside of it.
+    pub fn cell_at(&self, offset: Point) -> Cell {
+        match self.index(offset) {
+            Some(i) => self.cells[i],
+            None => cell(CellKind::Air),
+        }
+    }
+
+    /// Sets the cell at `offset` from the bottom left of the stamp, ignored outside of it.
+    pub fn set_cell(&mut self, offset: Point, cell: Cell) {
+        if let Some(i) = self.index(offset) {
+            self.cells[i] = cell;
+        }
+    }
+
+    pub fn kind_at(&self, offset: Point) -> CellKind {
+        self.cell_at(offset).kind()
+    }
+
+    /// Sets the cell at `offset` to a fresh cell of `kind`.
+    pub fn set_kind(&mut self, offset: Point, kind: CellKind) {
+        self.set_cell(offset, cell(kind));
+    }
+
+    /// Turns the stamp anticlockwise by `quarter_turns` of 90°, negative turns go clockwise.
+    pub fn rotate(self, quarter_turns: i32) -> Stamp {
+        match quarter_turns.rem_euclid(4) {
+            0 => self,
+            1 => self.remap(self.height, self.width, |x, y| {
+                point(self.height - 1 - y, x)
+            }),
+            2 => self.remap(self.width, self.height, |x, y| {
+                point(self.width - 1 - x, self.height - 1 - y)
+            }),
+            _ => self.remap(self.height, self.width, |x, y| point(y, self.width - 1 - x)),
+        }
+    }
+
+    /// Mirrors the stamp so its left and right sides swap.
+    pub fn flip_horizontal(self) -> Stamp {
+        self.remap(self.width, self.height, |x, y| point(self.width - 1 - x, y))
+    }
+
+    /// Mirrors the stamp so its top and bottom swap.
+    pub fn flip_vertical(self) -> Stamp {
+        self.remap(self.width, self.height, |x, y| {
+            point(x, self.height - 1 - y)
+        })
+    }
+
+    /// A `width` x `height` stamp with each cell of this one moved to `to(x, y)`.
+    fn remap(&self, width: i32, height: i32, to: impl Fn(i32, i32) -> Point) -> Stamp {
+        let mut stamp = Stamp::new(width, height);
+
+        for p in self.rect().points() {
+            let (x, y) = p.tup();
+            stamp.set_cell(to(x, y), self.cell_at(p));
+        }
+
+        stamp
+    }
+
+    /// The stamp's own rectangle, with its bottom left at the origin.
+    fn rect(&self) -> Rect {
+        Rect::new(point(0, 0), point(self.width, self.height))
+    }
+
+    fn index(&self, offset: Point) -> Option<usize> {
+        if !self.rect().contains(offset) {
+            return None;
+        }
+
+        offset.index(self.height as usize)
+    }
+}
+
+impl<S: CellStore> Cells<S> {
+    /// Copies `rect` of the world into a stamp, keeping each cell's kind and temperature.
+    pub fn copy(&self, rect: Rect) -> Stamp {
+        let mut stamp = Stamp::new(rect.width(), rect.height());
+
+        for (p, copied) in self.iter_rect(rect) {
+            let copied = cell(copied.kind()).with_temperature(copied.temperature());
+            stamp.set_cell(p - rect.min(), copied);
+        }
+
+        stamp
+    }
+
+    /// Writes `stamp` into the world with its bottom left at `at`, waking every cell it changes
+    /// like `set_cell` does.
+    pub fn paste(&mut self, stamp: &Stamp, at: Point, blend: Blend) {
+        for offset in stamp.rect().points() {
+            let pasted = stamp.cell_at(offset);
+            let target = at + offset;
+            let existing = self.cell_at(target);
+
+            let write = match blend {
+                Blend::Overwrite => true,
+                Blend::IntoAir => existing.is_air(),
+                Blend::SkipAir => pasted.not_air(),
+            };
+
+            let changes = existing.kind() != pasted.kind()
+                || (pasted.not_air() && existing.temperature() != pasted.temperature());
+            if write && changes {
+                self.set_cell(target, pasted);
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt::Debug;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    cells::{Cell, CellKind},
+    point::{Point, Rect, point},
+};
+
+const AIR: Cell = Cell::new(CellKind::Air);
+
+/// Where the cells of a world are kept.
+///
+/// Stores only ever hold non-air cells, every point that has nothing stored at it is air.
+pub trait CellStore: Clone + Debug {
+    fn cell_at(&self, point: Point) -> Cell;
+
+    /// `None` if there is no cell stored at `point`.
+    fn cell_at_mut(&mut self, point: Point) -> Option<&mut Cell>;
+
+    /// Storing air removes whatever was at `point`.
+    fn set_cell(&mut self, point: Point, cell: Cell);
+
+    fn swap_cells(&mut self, a: Point, b: Point) {
+        let a_cell = self.cell_at(a);
+        self.set_cell(a, self.cell_at(b));
+        self.set_cell(b, a_cell);
+    }
+
+    /// Every stored (non-air) cell, in no particular order.
+    fn iter(&self) -> impl Iterator<Item = (Point, Cell)> + '_;
+
+    /// Every stored (non-air) cell inside `rect`, in no particular order.
+    fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        self.iter().filter(move |&(point, _)| rect.contains(point))
+    }
+
+    /// Number of stored (non-air) cells.
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops any air the store is holding on to and frees memory it no longer needs.
+    fn compact(&mut self) {}
+}
+
+/// Side length of a square chunk of cells.
+const CHUNK_SIZE: i32 = 64;
+const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// Splits a world point into the coordinate of its chunk and its index within that chunk.
+#[inline]
+fn chunk_coords(p: Point) -> (Point, usize) {
+    let (x, y) = p.tup();
+    let chunk = point(x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE));
+    let local = (x.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + y.rem_euclid(CHUNK_SIZE)) as usize;
+    (chunk, local)
+}
+
+/// A dense block of `CHUNK_SIZE` x `CHUNK_SIZE` cells.
+#[derive(Debug, Clone)]
+struct Chunk {
+    cells: Box<[Cell]>,
+    /// Number of non-air cells, the chunk is freed when this reaches zero.
+    population: usize,
+}
+
+impl Chunk {
+    fn new() -> Self {
+        Self {
+            cells: vec![AIR; CHUNK_AREA].into_boxed_slice(),
+            population: 0,
+        }
+    }
+}
+
+/// Unbounded store made of dense chunks that are allocated on demand and freed once they are
+/// all air. A good default for worlds of any size.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkStore {
+    chunks: FxHashMap<Point, Chunk>,
+}
+
+impl ChunkStore {
+    /// Number of chunks currently allocated.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+impl CellStore for ChunkStore {
+    #[inline]
+    fn cell_at(&self, point: Point) -> Cell {
+        let (chunk, local) = chunk_coords(point);
+        match self.chunks.get(&chunk) {
+            Some(chunk) => chunk.cells[local],
+            None => AIR,
+        }
+    }
+
+    fn cell_at_mut(&mut self, point: Point) -> Option<&mut Cell> {
+        let (chunk, local) = chunk_coords(point);
+        let cell = &mut self.chunks.get_mut(&chunk)?.cells[local];
+        cell.not_air().then_some(cell)
+    }
+
+    fn set_cell(&mut self, point: Point, cell: Cell) {
+        let (coords, local) = chunk_coords(point);
+
+        let chunk = match self.chunks.get_mut(&coords) {
+            Some(chunk) => chunk,
+            None if cell.is_air() => return,
+            None => self.chunks.entry(coords).or_insert_with(Chunk::new),
+        };
+
+        let old = std::mem::replace(&mut chunk.cells[local], cell);
+
+        match (old.is_air(), cell.is_air()) {
+            (true, false) => chunk.population += 1,
+            (false, true) => chunk.population -= 1,
+            _ => (),
+        }
+
+        if chunk.population == 0 {
+            self.chunks.remove(&coords);
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        self.chunks.iter().flat_map(|(&coords, chunk)| {
+            let (chunk_x, chunk_y) = coords.tup();
+            chunk
+                .cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.not_air())
+                .map(move |(i, &cell)| {
+                    let local = point(i as i32 / CHUNK_SIZE, i as i32 % CHUNK_SIZE);
+                    (CHUNK_SIZE * point(chunk_x, chunk_y) + local, cell)
+                })
+        })
+    }
+
+    fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        let touched: Vec<(Point, &Chunk)> = if rect.is_empty() {
+            Vec::new()
+        } else {
+            let low = chunk_coords(rect.min()).0;
+            let high = chunk_coords(rect.max() - point(1, 1)).0;
+            let range = Rect::new(low, high + point(1, 1));
+
+            // Look the chunks under `rect` up directly, unless there are fewer chunks in the
+            // world than there are under `rect`.
+            let area = range.width() as u64 * range.height() as u64;
+            if area <= self.chunks.len() as u64 {
+                range
+                    .points()
+                    .filter_map(|coords| Some((coords, self.chunks.get(&coords)?)))
+                    .collect()
+            } else {
+                self.chunks
+                    .iter()
+                    .filter(|&(&coords, _)| range.contains(coords))
+                    .map(|(&coords, chunk)| (coords, chunk))
+                    .collect()
+            }
+        };
+
+        touched.into_iter().flat_map(move |(coords, chunk)| {
+            let origin = CHUNK_SIZE * coords;
+            let chunk_rect = Rect::new(origin, origin + point(CHUNK_SIZE, CHUNK_SIZE));
+
+            rect.intersection(chunk_rect)
+                .into_iter()
+                .flat_map(Rect::points)
+                .map(move |p| (p, chunk.cells[chunk_coords(p).1]))
+                .filter(|(_, cell)| cell.not_air())
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.population).sum()
+    }
+
+    fn compact(&mut self) {
+        self.chunks.retain(|_, chunk| {
+            chunk.population = chunk.cells.iter().filter(|cell| cell.not_air()).count();
+            chunk.population > 0
+        });
+        self.chunks.shrink_to_fit();
+    }
+}
+
+/// Unbounded store keeping each cell in a hash map. Good for huge worlds that are mostly empty.
+#[derive(Debug, Clone, Default)]
+pub struct SparseStore {
+    cells: FxHashMap<Point, Cell>,
+}
+
+impl CellStore for SparseStore {
+    #[inline]
+    fn cell_at(&self, point: Point) -> Cell {
+        self.cells.get(&point).copied().unwrap_or(AIR)
+    }
+
+    fn cell_at_mut(&mut self, point: Point) -> Option<&mut Cell> {
+        self.cells.get_mut(&point)
+    }
+
+    fn set_cell(&mut self, point: Point, cell: Cell) {
+        if cell.is_air() {
+            self.cells.remove(&point);
+        } else {
+            self.cells.insert(point, cell);
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        self.cells.iter().map(|(&point, &cell)| (point, cell))
+    }
+
+    fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn compact(&mut self) {
+        self.cells.retain(|_, cell| cell.not_air());
+        self.cells.shrink_to_fit();
+    }
+}
+
+/// Store holding every cell of a fixed rectangle in one grid. Good for small, full, bounded worlds.
+///
+/// Everything outside of the rectangle is air and cells set there are discarded, so it is meant to
+/// be used with world bounds that match it, see `Cells::dense`.
+#[derive(Debug, Clone)]
+pub struct DenseStore {
+    rect: Rect,
+    cells: Vec<Cell>,
+}
+
+impl DenseStore {
+    pub fn new(rect: Rect) -> DenseStore {
+        let area = rect.width().max(0) as usize * rect.height().max(0) as usize;
+
+        DenseStore {
+            rect,
+            cells: vec![AIR; area],
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    #[inline]
+    fn index(&self, p: Point) -> Option<usize> {
+        if !self.rect.contains(p) {
+            return None;
+        }
+
+        (p - self.rect.min()).index(self.rect.height() as usize)
+    }
+}
+
+impl CellStore for DenseStore {
+    #[inline]
+    fn cell_at(&self, point: Point) -> Cell {
+        match self.index(point) {
+            Some(i) => self.cells[i],
+            None => AIR,
+        }
+    }
+
+    fn cell_at_mut(&mut self, point: Point) -> Option<&mut Cell> {
+        let i = self.index(point)?;
+        let cell = &mut self.cells[i];
+        cell.not_air().then_some(cell)
+    }
+
+    fn set_cell(&mut self, point: Point, cell: Cell) {
+        if let Some(i) = self.index(point) {
+            self.cells[i] = cell;
+        }
+    }
+
+    fn swap_cells(&mut self, a: Point, b: Point) {
+        match (self.index(a), self.index(b)) {
+            (Some(a), Some(b)) => self.cells.swap(a, b),
+            (Some(a), None) => self.cells[a] = AIR,
+            (None, Some(b)) => self.cells[b] = AIR,
+            (None, None) => (),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        let height = self.rect.height();
+        let (min_x, min_y) = self.rect.min().tup();
+
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.not_air())
+            .map(move |(i, &cell)| {
+                let i = i as i32;
+                (point(min_x + i / height, min_y + i % height), cell)
+            })
+    }
+
+    fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = (Point, Cell)> + '_ {
+        rect.intersection(self.rect)
+            .into_iter()
+            .flat_map(Rect::points)
+            .map(|point| (point, self.cell_at(point)))
+            .filter(|(_, cell)| cell.not_air())
+    }
+}
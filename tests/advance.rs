@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use the_pink_sand_simulator::cells::Cells;
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+fn world() -> Cells {
+    let mut cells = Cells::with_seed(0);
+    cells.set_timestep(ms(10));
+    cells
+}
+
+#[test]
+fn one_tick_runs_per_timestep() {
+    let mut cells = world();
+
+    assert_eq!(cells.advance(ms(10)), 1);
+    assert_eq!(cells.advance(ms(30)), 3);
+    assert_eq!(cells.advance(Duration::ZERO), 0);
+    assert_eq!(cells.tick(), 4);
+}
+
+#[test]
+fn leftover_time_carries_over() {
+    let mut cells = world();
+
+    assert_eq!(cells.advance(ms(6)), 0);
+    assert_eq!(cells.advance(ms(6)), 1);
+    assert_eq!(cells.advance(ms(8)), 1);
+    assert_eq!(cells.advance(ms(19)), 1);
+    assert_eq!(cells.advance(ms(1)), 1);
+    assert_eq!(cells.tick(), 4);
+}
+
+#[test]
+fn catching_up_is_capped() {
+    let mut cells = world();
+
+    assert_eq!(cells.advance(Duration::from_secs(1)), 5);
+    assert_eq!(cells.tick(), 5);
+
+    cells.set_max_catch_up(2);
+    assert_eq!(cells.advance(Duration::from_secs(1)), 2);
+    assert_eq!(cells.tick(), 7);
+}
+
+#[test]
+fn time_past_the_cap_is_dropped() {
+    let mut cells = world();
+    cells.set_max_catch_up(2);
+
+    assert_eq!(cells.advance(ms(35)), 2);
+    // The 15ms left over was more than a timestep, so all of it went.
+    assert_eq!(cells.advance(ms(5)), 0);
+    assert_eq!(cells.advance(ms(5)), 1);
+}
+
+#[test]
+fn time_short_of_a_tick_past_the_cap_is_kept() {
+    let mut cells = world();
+    cells.set_max_catch_up(2);
+
+    assert_eq!(cells.advance(ms(25)), 2);
+    assert_eq!(cells.advance(ms(5)), 1);
+}
+
+#[test]
+fn the_default_timestep_is_a_sixtieth_of_a_second() {
+    let mut cells = Cells::with_seed(0);
+
+    assert_eq!(cells.timestep(), Duration::from_secs(1) / 60);
+    assert_eq!(cells.advance(Duration::from_secs(1) / 20), 3);
+}
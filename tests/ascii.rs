@@ -0,0 +1,107 @@
+use the_pink_sand_simulator::{
+    ascii::{AsciiError, Legend},
+    cells::{CellKind::*, Cells},
+    point::{Rect, point},
+};
+
+#[test]
+fn pictures_round_trip() {
+    let picture = "\
+#h    #
+#  .. #
+#~~~~~#
+#######";
+
+    let cells = Cells::from_ascii(picture, &Legend::default()).unwrap();
+
+    assert_eq!(
+        cells.to_ascii(Rect::new(point(0, 0), point(7, 4)), &Legend::default()),
+        picture
+    );
+}
+
+#[test]
+fn sand_falls_to_the_bottom_of_a_shaft() {
+    let mut cells = Cells::from_ascii(
+        r"
+#.#
+# #
+# #
+# #
+###
+",
+        &Legend::default(),
+    )
+    .unwrap();
+
+    for _ in 0..20 {
+        cells.update_all();
+    }
+
+    cells.assert_ascii(
+        r"
+# #
+# #
+# #
+#.#
+###
+",
+        &Legend::default(),
+    );
+}
+
+#[test]
+fn water_levels_out_in_a_tank() {
+    let mut cells = Cells::from_ascii(
+        r"
+# ~~ #
+# ~~ #
+######
+",
+        &Legend::default(),
+    )
+    .unwrap();
+
+    for _ in 0..20 {
+        cells.update_all();
+    }
+
+    cells.assert_ascii(
+        r"
+#    #
+#~~~~#
+######
+",
+        &Legend::default(),
+    );
+}
+
+#[test]
+fn custom_legends_replace_symbols() {
+    let legend = Legend::default().with('S', Sand);
+    let cells = Cells::from_ascii("S.", &legend).unwrap();
+
+    assert!(cells.cell_at(point(0, 0)).is(&[Sand]));
+    assert!(cells.cell_at(point(1, 0)).is(&[Sand]));
+}
+
+#[test]
+fn unknown_symbols_are_reported() {
+    let result = Cells::from_ascii("##\n#?", &Legend::default());
+
+    assert_eq!(
+        result.err(),
+        Some(AsciiError::UnknownSymbol {
+            symbol: '?',
+            line: 2,
+            column: 2,
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "world does not match the picture")]
+fn mismatches_panic() {
+    let cells = Cells::from_ascii("~~", &Legend::default()).unwrap();
+    cells.assert_ascii("~.", &Legend::default());
+}
@@ -0,0 +1,67 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, cell},
+    point::{Rect, point},
+    store::CellStore,
+};
+
+fn world(boundary: Boundary) -> Cells {
+    Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(10, 10)), boundary)
+}
+
+#[test]
+fn walls_hold_everything_in() {
+    let mut cells = world(Boundary::Wall);
+    cells.fill_rect(
+        Rect::new(point(0, 5), point(10, 10)),
+        cell(Water),
+        Default::default(),
+    );
+
+    cells.step(200);
+
+    assert_eq!(cells.count(Water), 50);
+    assert!(cells.iter().all(|(p, _)| p.tup().1 < 5));
+    assert_eq!(cells.cell_at(point(-1, 0)).kind(), Bedrock);
+}
+
+#[test]
+fn the_void_deletes_what_falls_into_it() {
+    let mut cells = world(Boundary::Void);
+    cells.fill_rect(
+        Rect::new(point(0, 5), point(10, 10)),
+        cell(Sand),
+        Default::default(),
+    );
+
+    cells.step(50);
+
+    assert_eq!(cells.store().len(), 0);
+    assert!(cells.cell_at(point(5, -1)).is_air());
+}
+
+#[test]
+fn wrapped_edges_are_joined() {
+    let mut cells = world(Boundary::Wrap);
+    cells.set_cell(point(3, 0), cell(Sand));
+
+    assert_eq!(cells.cell_at(point(13, 10)).kind(), Sand);
+
+    cells.update_all();
+
+    assert_eq!(cells.count(Sand), 1);
+    assert_eq!(cells.cell_at(point(3, 9)).kind(), Sand);
+}
+
+#[test]
+fn writes_outside_of_walls_are_discarded() {
+    let mut cells = world(Boundary::Wall);
+    cells.set_cell(point(20, 20), cell(Sand));
+
+    assert_eq!(cells.store().len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "are empty")]
+fn empty_bounds_are_rejected() {
+    let _ = Cells::bounded(Rect::new(point(0, 0), point(0, 10)), Boundary::Wrap);
+}
@@ -0,0 +1,158 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellError, CellKind::*, Cells, cell},
+    material::Material,
+    point::{Rect, point},
+    store::CellStore,
+};
+
+/// A registered material that can be locked or not.
+struct Marble {
+    locked: bool,
+}
+
+impl Material for Marble {
+    fn name(&self) -> &str {
+        "Marble"
+    }
+
+    fn colour(&self) -> [u8; 4] {
+        [230, 230, 220, 255]
+    }
+
+    fn density(&self) -> i32 {
+        400
+    }
+
+    fn locked(&self) -> bool {
+        self.locked
+    }
+}
+
+fn bounded() -> Cells {
+    Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(10, 10)), Boundary::Wall)
+}
+
+#[test]
+fn missing_cells_are_refused() {
+    let mut cells = bounded();
+
+    assert_eq!(
+        cells.try_swap(point(1, 1), point(2, 2)),
+        Err(CellError::Missing(point(1, 1)))
+    );
+    assert_eq!(
+        cells.try_update(point(1, 1), |_| ()),
+        Err(CellError::Missing(point(1, 1)))
+    );
+}
+
+#[test]
+fn out_of_bounds_points_are_refused() {
+    let mut cells = bounded();
+    cells.set_cell(point(1, 1), cell(Sand));
+
+    assert_eq!(
+        cells.try_set(point(-1, 5), cell(Sand)),
+        Err(CellError::OutOfBounds(point(-1, 5)))
+    );
+    assert_eq!(
+        cells.try_swap(point(1, 1), point(1, 10)),
+        Err(CellError::OutOfBounds(point(1, 10)))
+    );
+    assert_eq!(cells.cell_at(point(1, 1)).kind(), Sand);
+}
+
+#[test]
+fn locked_cells_are_refused() {
+    let mut cells = bounded();
+    cells.set_cell(point(1, 1), cell(Bedrock));
+    cells.set_cell(point(2, 1), cell(Sand));
+
+    assert_eq!(
+        cells.try_set(point(1, 1), cell(Sand)),
+        Err(CellError::Locked(point(1, 1)))
+    );
+    assert_eq!(
+        cells.try_swap(point(2, 1), point(1, 1)),
+        Err(CellError::Locked(point(1, 1)))
+    );
+    assert_eq!(
+        cells.try_update(point(1, 1), |c| *c = cell(Air)),
+        Err(CellError::Locked(point(1, 1)))
+    );
+    assert_eq!(cells.cell_at(point(1, 1)).kind(), Bedrock);
+}
+
+#[test]
+fn registered_materials_can_be_locked() {
+    let mut cells = bounded();
+    let locked = cells.register(Marble { locked: true });
+    let unlocked = cells.register(Marble { locked: false });
+    cells.set_cell(point(1, 1), cell(locked));
+    cells.set_cell(point(2, 1), cell(unlocked));
+
+    assert_eq!(
+        cells.try_set(point(1, 1), cell(Sand)),
+        Err(CellError::Locked(point(1, 1)))
+    );
+    assert_eq!(
+        cells.try_swap(point(2, 1), point(1, 1)),
+        Err(CellError::Locked(point(1, 1)))
+    );
+    assert_eq!(cells.try_set(point(2, 1), cell(Sand)), Ok(()));
+}
+
+#[test]
+fn configs_can_lock_and_unlock() {
+    let mut cells = bounded();
+    cells.set_cell(point(1, 1), cell(Bedrock));
+
+    // Retuning bedrock leaves it locked.
+    let retune = "[[material]]\nname = \"Bedrock\"\ncolour = [0, 0, 0, 255]\ndensity = 500";
+    cells.load_materials(retune).unwrap();
+    assert_eq!(
+        cells.try_set(point(1, 1), cell(Sand)),
+        Err(CellError::Locked(point(1, 1)))
+    );
+
+    cells
+        .load_materials(&format!("{retune}\nlocked = false"))
+        .unwrap();
+    assert_eq!(cells.try_set(point(1, 1), cell(Sand)), Ok(()));
+
+    let glass = "[[material]]\nname = \"Glass\"\ncolour = [0, 0, 0, 255]\ndensity = 50\n\
+                 movement = \"static\"\nlocked = true";
+    let glass = cells.load_materials(glass).unwrap()[0];
+    cells.set_cell(point(2, 2), cell(glass));
+    assert_eq!(
+        cells.try_update(point(2, 2), |c| *c = cell(Water)),
+        Err(CellError::Locked(point(2, 2)))
+    );
+}
+
+#[test]
+fn checked_changes_go_through() {
+    let mut cells = bounded();
+    cells.set_cell(point(1, 1), cell(Sand));
+
+    assert_eq!(cells.try_swap(point(1, 1), point(3, 3)), Ok(()));
+    assert_eq!(cells.cell_at(point(3, 3)).kind(), Sand);
+    assert!(cells.cell_at(point(1, 1)).is_air());
+
+    assert_eq!(cells.try_set(point(1, 1), cell(Water)), Ok(()));
+    assert_eq!(cells.cell_at(point(1, 1)).kind(), Water);
+}
+
+#[test]
+fn updating_to_air_frees_the_cell() {
+    let mut cells = Cells::new();
+    cells.set_cell(point(0, 0), cell(Sand));
+
+    assert_eq!(cells.try_update(point(0, 0), |c| *c = cell(Air)), Ok(()));
+    assert_eq!(cells.store().len(), 0);
+    assert_eq!(cells.store().chunk_count(), 0);
+
+    cells.set_cell(point(0, 0), cell(Sand));
+    cells.erase(point(0, 0));
+    assert_eq!(cells.store().len(), 0);
+}
@@ -0,0 +1,242 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind, Cells, cell},
+    material::{ConfigError, Materials},
+    point::{Rect, point},
+};
+
+/// A material table with `fields` as well as a colour and movement.
+fn material(fields: &str) -> String {
+    format!("[[material]]\ncolour = [1, 2, 3, 255]\nmovement = \"fall_tumble\"\n{fields}\n")
+}
+
+/// A valid material called Rust.
+fn rust() -> String {
+    material("name = \"Rust\"\ndensity = 30")
+}
+
+fn invalid(text: &str) -> (String, String) {
+    match Cells::new().load_materials(text) {
+        Err(ConfigError::Invalid { entry, reason }) => (entry, reason),
+        other => panic!("expected an invalid entry, got {other:?}"),
+    }
+}
+
+#[test]
+fn the_shipped_config_loads() {
+    let mut cells = Cells::new();
+    let mut loaded = cells
+        .load_materials(include_str!("../materials.toml"))
+        .unwrap();
+
+    let mut built_in: Vec<_> = CellKind::ALL
+        .into_iter()
+        .filter(|&kind| kind != CellKind::Air)
+        .collect();
+    built_in.sort_by_key(|kind| kind.name());
+    loaded.sort_by_key(|kind| kind.name());
+    assert_eq!(loaded, built_in);
+
+    assert_eq!(cells.materials().kinds().count(), CellKind::ALL.len());
+    let water = cells.materials().get(CellKind::Water);
+    assert_eq!(water.transitions().len(), 2);
+}
+
+#[test]
+fn the_shipped_config_matches_the_built_in_kinds() {
+    let built_in = Materials::default();
+    let mut loaded = Materials::default();
+    loaded.load(include_str!("../materials.toml")).unwrap();
+
+    for kind in CellKind::ALL {
+        assert_eq!(loaded.colour(kind), built_in.colour(kind), "{kind}");
+        assert_eq!(loaded.density(kind), built_in.density(kind), "{kind}");
+        assert_eq!(
+            loaded.heat_capacity(kind),
+            built_in.heat_capacity(kind),
+            "{kind}"
+        );
+        assert_eq!(
+            loaded.conductivity(kind),
+            built_in.conductivity(kind),
+            "{kind}"
+        );
+        assert_eq!(
+            loaded.get(kind).transitions(),
+            built_in.get(kind).transitions(),
+            "{kind}"
+        );
+        assert_eq!(
+            loaded.get(kind).update_first(),
+            built_in.get(kind).update_first(),
+            "{kind}"
+        );
+        assert_eq!(
+            loaded.get(kind).locked(),
+            built_in.get(kind).locked(),
+            "{kind}"
+        );
+    }
+}
+
+/// A seeded world with every built-in kind in it, some of them cold, after 150 ticks.
+fn simulate(config: Option<&str>) -> Vec<((i32, i32), CellKind)> {
+    let mut cells =
+        Cells::with_seed(5).with_bounds(Rect::new(point(-30, 0), point(30, 60)), Boundary::Wall);
+    if let Some(text) = config {
+        cells.load_materials(text).unwrap();
+    }
+
+    let kinds = CellKind::ALL
+        .into_iter()
+        .filter(|&kind| kind != CellKind::Air);
+    for (i, kind) in kinds.enumerate() {
+        let x = -30 + 5 * i as i32;
+        cells.fill_rect(
+            Rect::new(point(x, 20), point(x + 4, 40)),
+            cell(kind),
+            Default::default(),
+        );
+    }
+    cells.fill_rect(
+        Rect::new(point(-10, 45), point(10, 50)),
+        cell(CellKind::Honey).with_temperature(-20.0),
+        Default::default(),
+    );
+    cells.step(150);
+
+    let mut cells: Vec<_> = cells
+        .iter()
+        .map(|(p, cell)| (p.tup(), cell.kind()))
+        .collect();
+    cells.sort_unstable_by_key(|&(p, _)| p);
+    cells
+}
+
+#[test]
+fn loading_the_shipped_config_changes_nothing() {
+    assert_eq!(
+        simulate(Some(include_str!("../materials.toml"))),
+        simulate(None)
+    );
+}
+
+#[test]
+fn retuned_kinds_keep_moving_the_same_way() {
+    let mut materials = Materials::default();
+    let text = "[[material]]\nname = \"Hydrogen\"\ncolour = [1, 2, 3, 255]\ndensity = 6";
+
+    assert_eq!(materials.load(text).unwrap(), [CellKind::Hydrogen]);
+    assert_eq!(materials.colour(CellKind::Hydrogen), [1, 2, 3, 255]);
+    assert_eq!(materials.density(CellKind::Hydrogen), 6);
+    assert_eq!(
+        materials.get(CellKind::Hydrogen).update_first(),
+        Materials::default().get(CellKind::Hydrogen).update_first()
+    );
+}
+
+#[test]
+fn movement_is_only_given_for_new_materials() {
+    let (entry, reason) = invalid(&material("name = \"Sand\"\ndensity = 30"));
+    assert_eq!(entry, "\"Sand\"");
+    assert_eq!(reason, "a known material keeps its movement");
+
+    let text = "[[material]]\nname = \"Rust\"\ncolour = [1, 2, 3, 255]\ndensity = 30";
+    let (entry, reason) = invalid(text);
+    assert_eq!(entry, "\"Rust\"");
+    assert!(reason.contains("movement"), "{reason}");
+}
+
+#[test]
+fn new_materials_are_registered() {
+    let mut cells = Cells::new();
+    let kinds = cells.load_materials(&rust()).unwrap();
+
+    assert_eq!(kinds, [CellKind::Custom(0)]);
+    assert_eq!(cells.materials().find("Rust"), Some(CellKind::Custom(0)));
+    assert_eq!(
+        cells.materials().colour(CellKind::Custom(0)),
+        [1, 2, 3, 255]
+    );
+}
+
+#[test]
+fn bad_toml_is_a_syntax_error() {
+    let mut cells = Cells::new();
+
+    assert!(matches!(
+        cells.load_materials("[[material]"),
+        Err(ConfigError::Syntax(_))
+    ));
+    assert!(matches!(
+        cells.load_materials("colour = 1"),
+        Err(ConfigError::Syntax(_))
+    ));
+}
+
+#[test]
+fn entries_are_named_in_errors() {
+    let (entry, reason) = invalid(&material("density = 30"));
+    assert_eq!(entry, "#1");
+    assert!(reason.contains("name"), "{reason}");
+
+    let (entry, reason) = invalid(&(rust() + &material("name = \"Tin\"\ndensity = 30\nspeed = 3")));
+    assert_eq!(entry, "\"Tin\"");
+    assert!(reason.contains("speed"), "{reason}");
+}
+
+#[test]
+fn bad_values_are_refused() {
+    let cases = [
+        ("name = \" \"\ndensity = 30", "name must not be empty"),
+        ("name = \"Air\"\ndensity = 30", "air cannot be redefined"),
+        (
+            "name = \"Rust\"\ndensity = -1",
+            "density must not be negative",
+        ),
+        (
+            "name = \"Rust\"\ndensity = 30\nheat_capacity = 0.0",
+            "heat_capacity must be positive",
+        ),
+        (
+            "name = \"Rust\"\ndensity = 30\nconductivity = -0.5",
+            "conductivity must not be negative",
+        ),
+    ];
+
+    for (fields, expected) in cases {
+        let (_, reason) = invalid(&material(fields));
+        assert_eq!(reason, expected);
+    }
+}
+
+#[test]
+fn duplicates_are_refused() {
+    let (entry, reason) = invalid(&rust().repeat(2));
+
+    assert_eq!(entry, "\"Rust\"");
+    assert_eq!(reason, "is defined more than once");
+}
+
+#[test]
+fn transitions_are_checked() {
+    let transition = |fields: &str| format!("{}\n[[material.transition]]\n{fields}\n", rust());
+
+    let (_, reason) = invalid(&transition("into = \"Sand\""));
+    assert!(reason.contains("exactly one"), "{reason}");
+
+    let (_, reason) = invalid(&transition("below = 1.0\nabove = 2.0\ninto = \"Sand\""));
+    assert!(reason.contains("exactly one"), "{reason}");
+
+    let (_, reason) = invalid(&transition("below = 1.0\ninto = \"Nothing\""));
+    assert!(reason.contains("unknown material"), "{reason}");
+}
+
+#[test]
+fn nothing_is_added_from_an_invalid_file() {
+    let mut cells = Cells::new();
+    let text = rust() + &material("name = \"\"\ndensity = 30");
+
+    assert!(cells.load_materials(&text).is_err());
+    assert_eq!(cells.materials().kinds().count(), CellKind::ALL.len());
+    assert_eq!(cells.materials().find("Rust"), None);
+}
@@ -0,0 +1,69 @@
+use the_pink_sand_simulator::{
+    cells::{CellKind::*, Cells, cell},
+    point::{Rect, point},
+    store::{CellStore, SparseStore},
+};
+
+#[test]
+fn setting_air_stores_nothing() {
+    let mut cells = Cells::new();
+
+    for x in -100..100 {
+        cells.set_cell(point(x, 7 * x), cell(Air));
+    }
+
+    assert_eq!(cells.store().len(), 0);
+    assert_eq!(cells.store().chunk_count(), 0);
+}
+
+#[test]
+fn erasing_frees_everything() {
+    let mut cells = Cells::new();
+    let rect = Rect::new(point(-200, -50), point(200, 50));
+
+    cells.fill_rect(rect, cell(Sand), Default::default());
+    assert_eq!(cells.store().len(), 400 * 100);
+
+    cells.erase(point(0, 0));
+    assert_eq!(cells.store().len(), 400 * 100 - 1);
+
+    assert_eq!(cells.erase_rect(rect), 400 * 100 - 1);
+    assert_eq!(cells.store().len(), 0);
+    assert_eq!(cells.store().chunk_count(), 0);
+}
+
+#[test]
+fn moving_matter_leaves_no_air_behind() {
+    let mut cells = Cells::with_store(SparseStore::default(), 7);
+    cells.fill_rect(
+        Rect::new(point(0, 200), point(30, 230)),
+        cell(Water),
+        Default::default(),
+    );
+    cells.fill_rect(
+        Rect::new(point(-50, -1), point(80, 0)),
+        cell(Bedrock),
+        Default::default(),
+    );
+    let matter = cells.store().len();
+
+    for _ in 0..300 {
+        cells.update_all();
+        assert_eq!(cells.store().len(), matter);
+        assert_eq!(cells.store().iter().filter(|(_, c)| c.is_air()).count(), 0);
+    }
+
+    assert_eq!(cells.count(Water), 30 * 30);
+}
+
+#[test]
+fn compacting_forgets_empty_awake_points() {
+    let mut cells = Cells::new();
+    cells.set_cell(point(0, 0), cell(Sand));
+    cells.erase(point(0, 0));
+    assert!(cells.awake_count() > 0);
+
+    cells.compact();
+    assert_eq!(cells.awake_count(), 0);
+    assert_eq!(cells.store().len(), 0);
+}
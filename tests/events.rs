@@ -0,0 +1,139 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, Event, cell},
+    point::{Rect, point},
+};
+
+/// A shaft one cell wide, so anything in it can only move up or down.
+fn shaft(boundary: Boundary) -> Cells {
+    Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(1, 10)), boundary)
+}
+
+/// Steps until something happens, returning what did.
+fn next_events(cells: &mut Cells) -> Vec<Event> {
+    for _ in 0..100 {
+        cells.step(1);
+        let events: Vec<Event> = cells.drain_events().collect();
+        if !events.is_empty() {
+            return events;
+        }
+    }
+    panic!("nothing happened in 100 ticks");
+}
+
+#[test]
+fn falling_into_air_is_a_move() {
+    let mut cells = shaft(Boundary::Wall);
+    cells.set_cell(point(0, 5), cell(Sand));
+    cells.record_events();
+
+    assert_eq!(
+        next_events(&mut cells),
+        [Event::Moved {
+            from: point(0, 5),
+            to: point(0, 4),
+            kind: Sand,
+        }]
+    );
+}
+
+#[test]
+fn sinking_through_a_liquid_is_a_swap() {
+    let mut cells = shaft(Boundary::Wall);
+    cells.set_cell(point(0, 0), cell(Water));
+    cells.set_cell(point(0, 1), cell(Sand));
+    cells.record_events();
+
+    assert_eq!(
+        next_events(&mut cells),
+        [Event::Swapped {
+            from: point(0, 1),
+            to: point(0, 0),
+            kind: Sand,
+            other: Water,
+        }]
+    );
+}
+
+#[test]
+fn writing_creates_and_destroys() {
+    let mut cells = shaft(Boundary::Wall);
+    cells.record_events();
+
+    cells.set_cell(point(0, 3), cell(Sand));
+    cells.set_cell(point(0, 3), cell(Water));
+    cells.set_cell(point(0, 3), cell(Water));
+    cells.erase(point(0, 3));
+    cells.erase(point(0, 3));
+
+    let events: Vec<Event> = cells.drain_events().collect();
+    assert_eq!(
+        events,
+        [
+            Event::Created {
+                at: point(0, 3),
+                kind: Sand,
+            },
+            Event::Destroyed {
+                at: point(0, 3),
+                kind: Sand,
+            },
+            Event::Created {
+                at: point(0, 3),
+                kind: Water,
+            },
+            Event::Destroyed {
+                at: point(0, 3),
+                kind: Water,
+            },
+        ]
+    );
+}
+
+#[test]
+fn falling_into_the_void_destroys() {
+    let mut cells = shaft(Boundary::Void);
+    cells.set_cell(point(0, 0), cell(Sand));
+    cells.record_events();
+
+    assert_eq!(
+        next_events(&mut cells),
+        [Event::Destroyed {
+            at: point(0, 0),
+            kind: Sand,
+        }]
+    );
+    assert_eq!(cells.count(Sand), 0);
+}
+
+#[test]
+fn nothing_is_recorded_unless_asked() {
+    let mut cells = shaft(Boundary::Void);
+    cells.set_cell(point(0, 5), cell(Sand));
+    cells.step(20);
+
+    assert_eq!(cells.count(Sand), 0);
+    assert_eq!(cells.drain_events().count(), 0);
+}
+
+#[test]
+fn stopping_drops_undrained_events() {
+    let mut cells = shaft(Boundary::Wall);
+    cells.record_events();
+    cells.set_cell(point(0, 5), cell(Sand));
+
+    cells.stop_recording_events();
+    cells.set_cell(point(0, 6), cell(Sand));
+    cells.step(20);
+
+    assert_eq!(cells.drain_events().count(), 0);
+}
+
+#[test]
+fn drained_events_are_gone() {
+    let mut cells = shaft(Boundary::Wall);
+    cells.record_events();
+    cells.set_cell(point(0, 5), cell(Sand));
+
+    assert_eq!(cells.drain_events().count(), 1);
+    assert_eq!(cells.drain_events().count(), 0);
+}
@@ -0,0 +1,158 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, Replace, cell},
+    point::{Rect, circle, line, point},
+};
+
+fn rect(min: (i32, i32), max: (i32, i32)) -> Rect {
+    Rect::new(point(min.0, min.1), point(max.0, max.1))
+}
+
+#[test]
+fn filling_wakes_the_changed_cells_and_their_border() {
+    let mut cells = Cells::with_seed(0);
+
+    assert_eq!(
+        cells.fill_rect(rect((0, 0), (4, 3)), cell(Sand), Replace::Any),
+        12
+    );
+    // The 4 x 3 block and a ring one cell wide around it.
+    assert_eq!(cells.awake_count(), 6 * 5);
+}
+
+#[test]
+fn filling_what_is_already_there_changes_nothing() {
+    let mut cells = Cells::with_seed(0);
+    cells.fill_rect(rect((0, 0), (4, 3)), cell(Bedrock), Replace::Any);
+    cells.step(5);
+    assert_eq!(cells.awake_count(), 0);
+
+    assert_eq!(
+        cells.fill_rect(rect((0, 0), (4, 3)), cell(Bedrock), Replace::Any),
+        0
+    );
+    assert_eq!(cells.awake_count(), 0);
+}
+
+#[test]
+fn only_the_listed_kinds_are_replaced() {
+    let mut cells = Cells::with_seed(0);
+    cells.fill_rect(rect((0, 0), (3, 1)), cell(Water), Replace::Any);
+    cells.fill_rect(rect((0, 1), (3, 2)), cell(Bedrock), Replace::Any);
+
+    let only = [Air, Water];
+    let changed = cells.fill_rect(rect((0, 0), (3, 3)), cell(Honey), Replace::Only(&only));
+
+    assert_eq!(changed, 6);
+    assert_eq!(cells.count(Honey), 6);
+    assert_eq!(cells.count(Bedrock), 3);
+    assert_eq!(cells.count(Water), 0);
+}
+
+#[test]
+fn circles_fill_every_point_within_their_radius() {
+    let mut cells = Cells::with_seed(0);
+
+    let changed = cells.fill_circle(point(5, -5), 6, cell(Sand), Replace::Any);
+
+    assert_eq!(changed, circle(point(5, -5), 6).count());
+    assert!(circle(point(5, -5), 6).all(|p| cells.cell_at(p).kind() == Sand));
+    assert_eq!(
+        cells.fill_circle(point(40, 0), 0, cell(Sand), Replace::Any),
+        1
+    );
+}
+
+#[test]
+fn thin_lines_are_one_cell_thick() {
+    let mut cells = Cells::with_seed(0);
+    let (from, to) = (point(-3, 2), point(9, 7));
+
+    let changed = cells.draw_line(from, to, 0, cell(Bedrock), Replace::Any);
+
+    assert_eq!(changed, line(from, to).count());
+    assert!(line(from, to).all(|p| cells.cell_at(p).kind() == Bedrock));
+}
+
+#[test]
+fn thick_lines_are_swept_circles() {
+    let mut cells = Cells::with_seed(0);
+
+    // A circle of radius 1 is a 3 x 3 square, so this is a 3 cell thick bar with square ends.
+    let changed = cells.draw_line(point(0, 0), point(10, 0), 1, cell(Bedrock), Replace::Any);
+
+    assert_eq!(changed, 13 * 3);
+    assert_eq!(cells.occupied_bounds(), Some(rect((-1, -1), (12, 2))));
+}
+
+/// A 10 x 10 world split by a bedrock wall down `x = 5`.
+fn split_world(boundary: Boundary) -> Cells {
+    let mut cells = Cells::with_seed(0).with_bounds(rect((0, 0), (10, 10)), boundary);
+    cells.fill_rect(rect((5, 0), (6, 10)), cell(Bedrock), Replace::Any);
+    cells
+}
+
+#[test]
+fn flood_fills_stop_at_other_kinds() {
+    let mut cells = split_world(Boundary::Wall);
+
+    let changed = cells.flood_fill(
+        point(2, 2),
+        rect((0, 0), (10, 10)),
+        cell(Water),
+        Replace::Any,
+    );
+
+    assert_eq!(changed, 5 * 10);
+    assert_eq!(cells.cell_at(point(4, 9)).kind(), Water);
+    assert!(cells.cell_at(point(6, 0)).is_air());
+}
+
+#[test]
+fn flood_fills_cross_wrapped_edges() {
+    let mut cells = split_world(Boundary::Wrap);
+
+    let changed = cells.flood_fill(
+        point(2, 2),
+        rect((0, 0), (10, 10)),
+        cell(Water),
+        Replace::Any,
+    );
+
+    assert_eq!(changed, 9 * 10);
+    assert_eq!(cells.count(Bedrock), 10);
+}
+
+#[test]
+fn flood_fills_stay_in_their_area() {
+    let mut cells = Cells::with_seed(0);
+
+    let area = rect((-2, -2), (3, 3));
+    assert_eq!(
+        cells.flood_fill(point(0, 0), area, cell(Sand), Replace::Any),
+        25
+    );
+    assert_eq!(cells.occupied_bounds(), Some(area));
+
+    // Starting outside of the area, or on the kind being filled, does nothing.
+    assert_eq!(
+        cells.flood_fill(point(10, 0), area, cell(Water), Replace::Any),
+        0
+    );
+    assert_eq!(
+        cells.flood_fill(point(0, 0), area, cell(Sand), Replace::Any),
+        0
+    );
+}
+
+#[test]
+fn flood_fills_respect_replace() {
+    let mut cells = split_world(Boundary::Wall);
+
+    let only = [Water];
+    let area = rect((0, 0), (10, 10));
+    assert_eq!(
+        cells.flood_fill(point(2, 2), area, cell(Sand), Replace::Only(&only)),
+        0
+    );
+    assert_eq!(cells.count(Sand), 0);
+}
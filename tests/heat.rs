@@ -0,0 +1,79 @@
+use the_pink_sand_simulator::{
+    cells::{AMBIENT_TEMPERATURE, Boundary, CellError, CellKind::*, Cells, cell},
+    point::{Rect, point},
+};
+
+#[test]
+fn hot_cells_cool_towards_ambient() {
+    let mut cells = Cells::with_seed(0);
+    cells.set_cell(point(0, 0), cell(Bedrock).with_temperature(200.0));
+
+    let mut last = cells.temperature_at(point(0, 0));
+    for _ in 0..20 {
+        cells.step(100);
+        let temperature = cells.temperature_at(point(0, 0));
+        assert!(temperature <= last);
+        assert!(temperature >= AMBIENT_TEMPERATURE);
+        last = temperature;
+    }
+
+    assert!(last - AMBIENT_TEMPERATURE < 1.0);
+    assert_eq!(cells.temperature_at(point(0, 1)), AMBIENT_TEMPERATURE);
+}
+
+#[test]
+fn heat_sources_pin_their_point() {
+    let mut cells = Cells::with_seed(0);
+    cells.fill_rect(
+        Rect::new(point(0, 0), point(10, 1)),
+        cell(Bedrock),
+        Default::default(),
+    );
+    cells.add_heat_source(point(0, 0), 300.0);
+
+    cells.step(1000);
+
+    assert_eq!(cells.temperature_at(point(0, 0)), 300.0);
+    let along: Vec<f32> = (0..10).map(|x| cells.temperature_at(point(x, 0))).collect();
+    assert!(along.windows(2).all(|pair| pair[0] > pair[1]));
+    assert!(along[9] > AMBIENT_TEMPERATURE);
+
+    assert!(cells.remove_heat_source(point(0, 0)));
+    cells.step(5000);
+    assert!(cells.temperature_at(point(0, 0)) - AMBIENT_TEMPERATURE < 1.0);
+}
+
+#[test]
+fn heat_flows_from_hot_to_cold() {
+    let mut cells = Cells::with_seed(0);
+    cells.set_cell(point(0, 0), cell(Bedrock).with_temperature(100.0));
+    cells.set_cell(point(1, 0), cell(Bedrock).with_temperature(-100.0));
+
+    cells.step(20);
+
+    let (hot, cold) = (
+        cells.temperature_at(point(0, 0)),
+        cells.temperature_at(point(1, 0)),
+    );
+    assert!(hot < 100.0 && cold > -100.0);
+    assert!(hot > cold);
+}
+
+#[test]
+fn temperature_needs_a_cell() {
+    let mut cells =
+        Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(10, 10)), Boundary::Wall);
+
+    assert_eq!(
+        cells.set_temperature(point(1, 1), 50.0),
+        Err(CellError::Missing(point(1, 1)))
+    );
+    assert_eq!(
+        cells.set_temperature(point(20, 1), 50.0),
+        Err(CellError::OutOfBounds(point(20, 1)))
+    );
+
+    cells.set_cell(point(1, 1), cell(Bedrock));
+    assert_eq!(cells.set_temperature(point(1, 1), 50.0), Ok(()));
+    assert_eq!(cells.temperature_at(point(1, 1)), 50.0);
+}
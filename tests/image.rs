@@ -0,0 +1,92 @@
+use the_pink_sand_simulator::{
+    cells::{CellKind::*, Cells, cell},
+    image::{ImageError, Palette, UnknownColour},
+    material::Materials,
+    point::{Rect, point},
+};
+
+fn sorted(cells: &Cells) -> Vec<((i32, i32), String)> {
+    let mut all: Vec<_> = cells
+        .iter()
+        .map(|(p, cell)| (p.tup(), cell.kind().to_string()))
+        .collect();
+    all.sort();
+    all
+}
+
+fn picture() -> Cells {
+    let mut cells = Cells::with_seed(0);
+    cells.fill_rect(
+        Rect::new(point(-3, -2), point(4, 0)),
+        cell(Bedrock),
+        Default::default(),
+    );
+    cells.set_cell(point(0, 0), cell(Sand));
+    cells.set_cell(point(1, 3), cell(Water));
+    cells.set_cell(point(-3, 4), cell(Hydrogen));
+    cells
+}
+
+fn png(cells: &Cells, palette: &Palette) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    cells
+        .to_image(Rect::new(point(-3, -2), point(4, 5)), palette, &mut bytes)
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn images_round_trip() {
+    let cells = picture();
+    let bytes = png(&cells, &Palette::default());
+
+    let loaded = Cells::from_image(
+        bytes.as_slice(),
+        point(-3, -2),
+        &Palette::default(),
+        UnknownColour::Error,
+    )
+    .unwrap();
+
+    assert_eq!(sorted(&loaded), sorted(&cells));
+    assert_eq!(loaded.seed(), 0);
+}
+
+#[test]
+fn unknown_colours_can_be_refused() {
+    let bytes = png(&picture(), &Palette::new([([1, 2, 3, 255], Sand)]));
+
+    let result = Cells::from_image(
+        bytes.as_slice(),
+        point(0, 0),
+        &Palette::default(),
+        UnknownColour::Error,
+    );
+
+    // The sand is three columns in and four rows up from the bottom of a seven row image.
+    assert!(matches!(
+        result,
+        Err(ImageError::UnknownColour {
+            colour: [1, 2, 3, 255],
+            x: 3,
+            y: 4,
+        })
+    ));
+}
+
+#[test]
+fn unknown_colours_can_take_the_nearest_kind() {
+    let [r, g, b, a] = Materials::default().colour(Sand);
+    let palette = Palette::new([([r + 3, g - 2, b, a], Sand)]);
+    let bytes = png(&picture(), &palette);
+
+    let loaded = Cells::from_image(
+        bytes.as_slice(),
+        point(-3, -2),
+        &Palette::default(),
+        UnknownColour::Nearest,
+    )
+    .unwrap();
+
+    assert_eq!(sorted(&loaded), sorted(&picture()));
+}
@@ -0,0 +1,59 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, cell},
+    material::{Material, UpdateContext},
+    point::{DOWN, Point, Rect, point},
+};
+
+/// Sinks straight down through anything lighter.
+struct Lead;
+
+impl Material for Lead {
+    fn name(&self) -> &str {
+        "Lead"
+    }
+
+    fn colour(&self) -> [u8; 4] {
+        [90, 90, 100, 255]
+    }
+
+    fn density(&self) -> i32 {
+        50
+    }
+
+    fn update(&self, world: &mut UpdateContext<'_>, point: Point) {
+        if !world.multi_try_swap(point, &[DOWN]) {
+            world.rest(point, &[DOWN]);
+        }
+    }
+}
+
+#[test]
+fn registered_materials_are_looked_up_by_kind() {
+    let mut cells = Cells::new();
+    let lead = cells.register(Lead);
+
+    let materials = cells.materials();
+    assert_eq!(materials.colour(lead), [90, 90, 100, 255]);
+    assert_eq!(materials.density(lead), 50);
+    assert_eq!(materials.find("Lead"), Some(lead));
+}
+
+#[test]
+fn registered_materials_move() {
+    let mut cells =
+        Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(5, 20)), Boundary::Wall);
+    let lead = cells.register(Lead);
+
+    cells.fill_rect(
+        Rect::new(point(0, 0), point(5, 10)),
+        cell(Water),
+        Default::default(),
+    );
+    cells.set_cell(point(2, 15), cell(lead));
+
+    cells.step(100);
+
+    assert_eq!(cells.cell_at(point(2, 0)).kind(), lead);
+    assert_eq!(cells.count(lead), 1);
+    assert_eq!(cells.count(Water), 5 * 10);
+}
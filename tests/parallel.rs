@@ -0,0 +1,42 @@
+#![cfg(feature = "parallel")]
+
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, cell},
+    point::{Rect, point},
+    store::CellStore,
+};
+
+#[test]
+fn parallel_update_keeps_every_cell() {
+    let mut cells = Cells::with_seed(0).with_bounds(
+        Rect::new(point(-128, -128), point(128, 128)),
+        Boundary::Wall,
+    );
+
+    for x in -64..64_i32 {
+        for y in 0..48 {
+            let kind = match (x + y).rem_euclid(3) {
+                0 => Water,
+                1 => Sand,
+                _ => Hydrogen,
+            };
+            cells.set_cell(point(x, y), cell(kind));
+        }
+    }
+
+    let count = |cells: &Cells, kind| {
+        cells
+            .store()
+            .iter()
+            .filter(|(_, cell)| cell.is(&[kind]))
+            .count()
+    };
+    let before = [Water, Sand, Hydrogen].map(|kind| count(&cells, kind));
+
+    for _ in 0..50 {
+        cells.par_update_all();
+    }
+
+    let after = [Water, Sand, Hydrogen].map(|kind| count(&cells, kind));
+    assert_eq!(before, after);
+}
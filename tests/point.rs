@@ -0,0 +1,180 @@
+use the_pink_sand_simulator::point::{Point, Rect, circle, circle_outline, line, point};
+
+fn is_contiguous(points: &[Point]) -> bool {
+    points.windows(2).all(|pair| {
+        let (x, y) = (pair[1] - pair[0]).tup();
+        x.abs() <= 1 && y.abs() <= 1 && (x, y) != (0, 0)
+    })
+}
+
+#[test]
+fn lines_run_between_their_ends_in_every_octant() {
+    let from = point(3, -2);
+    let ends = [
+        (7, 2),
+        (2, 7),
+        (-2, 7),
+        (-7, 2),
+        (-7, -2),
+        (-2, -7),
+        (2, -7),
+        (7, -2),
+    ];
+
+    for (x, y) in ends {
+        let to = from + point(x, y);
+        let points: Vec<Point> = line(from, to).collect();
+
+        assert_eq!(points.first(), Some(&from), "line to {to:?}");
+        assert_eq!(points.last(), Some(&to), "line to {to:?}");
+        assert_eq!(
+            points.len() as i32,
+            x.abs().max(y.abs()) + 1,
+            "line to {to:?}"
+        );
+        assert!(is_contiguous(&points), "line to {to:?} has gaps");
+    }
+}
+
+#[test]
+fn lines_are_straight_along_the_axes_and_diagonals() {
+    let from = point(1, 1);
+
+    for to in [point(6, 1), point(1, -4), point(6, 6), point(-4, 6)] {
+        let (x, y) = (to - from).tup();
+        let step = point(x.signum(), y.signum());
+        let expected: Vec<Point> = (0..=5).map(|i| from + i * step).collect();
+        assert_eq!(line(from, to).collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+fn a_line_to_itself_is_one_point() {
+    assert_eq!(
+        line(point(4, 4), point(4, 4)).collect::<Vec<_>>(),
+        [point(4, 4)]
+    );
+}
+
+#[test]
+fn circles_are_symmetric() {
+    let centre = point(-5, 8);
+
+    for radius in 0..8 {
+        let points: Vec<Point> = circle(centre, radius).collect();
+        assert!(points.contains(&centre));
+
+        for &p in &points {
+            let (x, y) = (p - centre).tup();
+            for (x, y) in [(-x, y), (x, -y), (y, x), (-y, -x)] {
+                assert!(
+                    points.contains(&(centre + point(x, y))),
+                    "radius {radius} has {p:?} but not its mirror image"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn circles_reach_their_radius() {
+    let centre = point(2, 2);
+    let points: Vec<Point> = circle(centre, 4).collect();
+
+    for direction in [point(1, 0), point(0, 1), point(-1, 0), point(0, -1)] {
+        assert!(points.contains(&(centre + 4 * direction)));
+        assert!(!points.contains(&(centre + 5 * direction)));
+    }
+    assert_eq!(circle(centre, 0).collect::<Vec<_>>(), [centre]);
+    assert_eq!(circle(centre, -3).collect::<Vec<_>>(), [centre]);
+}
+
+#[test]
+fn outlines_are_the_edge_of_their_circle() {
+    let centre = point(0, 0);
+
+    for radius in 1..8 {
+        let disc: Vec<Point> = circle(centre, radius).collect();
+        let outline: Vec<Point> = circle_outline(centre, radius).collect();
+
+        assert!(outline.iter().all(|p| disc.contains(p)));
+        assert!(!outline.contains(&centre));
+        for p in [point(radius, 0), point(0, -radius), point(-radius, 0)] {
+            assert!(outline.contains(&p), "radius {radius} outline misses {p:?}");
+        }
+        // Every point of the disc not on the outline is surrounded by the disc.
+        for &p in disc.iter().filter(|p| !outline.contains(p)) {
+            for offset in [point(0, 1), point(0, -1), point(1, 0), point(-1, 0)] {
+                assert!(disc.contains(&(p + offset)));
+            }
+        }
+    }
+}
+
+#[test]
+fn overlapping_rects_intersect() {
+    let a = Rect::new(point(0, 0), point(10, 5));
+    let b = Rect::new(point(4, -3), point(20, 2));
+
+    let expected = Rect::new(point(4, 0), point(10, 2));
+    assert_eq!(a.intersection(b), Some(expected));
+    assert_eq!(b.intersection(a), Some(expected));
+    assert_eq!(a.intersection(a), Some(a));
+}
+
+#[test]
+fn separate_rects_do_not_intersect() {
+    let a = Rect::new(point(0, 0), point(10, 5));
+
+    // Touching along an edge shares no points.
+    assert_eq!(a.intersection(Rect::new(point(10, 0), point(12, 5))), None);
+    assert_eq!(a.intersection(Rect::new(point(0, 5), point(10, 8))), None);
+    assert_eq!(
+        a.intersection(Rect::new(point(20, 20), point(30, 30))),
+        None
+    );
+}
+
+#[test]
+fn empty_rects_intersect_nothing() {
+    let a = Rect::new(point(0, 0), point(10, 5));
+    let empty = Rect::new(point(3, 3), point(3, 4));
+    let inside_out = Rect::new(point(8, 4), point(2, 1));
+
+    assert!(empty.is_empty());
+    assert!(inside_out.is_empty());
+    assert_eq!(a.intersection(empty), None);
+    assert_eq!(a.intersection(inside_out), None);
+    assert_eq!(empty.intersection(empty), None);
+}
+
+#[test]
+fn rects_contain_rects_inside_them() {
+    let a = Rect::new(point(0, 0), point(10, 5));
+
+    assert!(a.contains_rect(a));
+    assert!(a.contains_rect(Rect::new(point(2, 1), point(10, 5))));
+    assert!(!a.contains_rect(Rect::new(point(2, 1), point(11, 5))));
+    assert!(!a.contains_rect(Rect::new(point(-1, 0), point(3, 3))));
+    assert!(!a.contains_rect(Rect::new(point(20, 20), point(30, 30))));
+}
+
+#[test]
+fn every_rect_contains_empty_rects() {
+    let a = Rect::new(point(0, 0), point(10, 5));
+    let far_away = Rect::new(point(50, 50), point(50, 60));
+
+    assert!(a.contains_rect(far_away));
+    assert!(far_away.contains_rect(far_away));
+    assert!(!far_away.contains_rect(a));
+}
+
+#[test]
+fn only_non_negative_points_convert_to_usizes() {
+    assert_eq!(point(0, 0).try_utup(), Some((0, 0)));
+    assert_eq!(point(7, 3).try_utup(), Some((7, 3)));
+    assert_eq!(point(-1, 3).try_utup(), None);
+    assert_eq!(point(3, -1).try_utup(), None);
+    assert_eq!(point(i32::MIN, i32::MAX).try_utup(), None);
+    assert_eq!(point(i32::MAX, 0).try_utup(), Some((i32::MAX as usize, 0)));
+}
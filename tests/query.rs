@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind, CellKind::*, Cells, cell},
+    point::{Point, Rect, point},
+    store::{CellStore, DenseStore, SparseStore},
+};
+
+/// Cells either side of the chunk edges at -128, -64, 0 and 64.
+const CELLS: [((i32, i32), CellKind); 10] = [
+    ((-129, 5), Sand),
+    ((-128, 5), Sand),
+    ((-65, -1), Water),
+    ((-64, -64), Water),
+    ((-65, -65), Honey),
+    ((-1, -1), Bedrock),
+    ((0, 0), Bedrock),
+    ((63, 63), Sand),
+    ((64, 0), PinkSand),
+    ((64, 64), Sand),
+];
+
+const WORLD: Rect = Rect::new(point(-200, -200), point(200, 200));
+
+fn fill<S: CellStore>(mut cells: Cells<S>) -> Cells<S> {
+    for ((x, y), kind) in CELLS {
+        cells.set_cell(point(x, y), cell(kind));
+    }
+    cells
+}
+
+fn chunked() -> Cells {
+    fill(Cells::with_seed(0))
+}
+
+fn sparse() -> Cells<SparseStore> {
+    fill(Cells::with_store(SparseStore::default(), 0))
+}
+
+fn dense() -> Cells<DenseStore> {
+    fill(Cells::with_store(DenseStore::new(WORLD), 0).with_bounds(WORLD, Boundary::Wall))
+}
+
+fn sorted(cells: impl Iterator<Item = (Point, CellKind)>) -> Vec<((i32, i32), CellKind)> {
+    let mut cells: Vec<_> = cells.map(|(p, kind)| (p.tup(), kind)).collect();
+    cells.sort_unstable_by_key(|&(p, _)| p);
+    cells
+}
+
+fn expected(rect: Rect) -> Vec<((i32, i32), CellKind)> {
+    sorted(
+        CELLS
+            .into_iter()
+            .map(|((x, y), kind)| (point(x, y), kind))
+            .filter(|&(p, _)| rect.contains(p)),
+    )
+}
+
+fn check_queries<S: CellStore>(cells: &Cells<S>) {
+    let everything = sorted(cells.iter().map(|(p, cell)| (p, cell.kind())));
+    assert_eq!(everything, expected(WORLD));
+
+    let rects = [
+        Rect::new(point(-70, -70), point(-60, -60)),
+        Rect::new(point(-65, -2), point(1, 1)),
+        Rect::new(point(-129, 0), point(-127, 10)),
+        Rect::new(point(-64, -64), point(64, 64)),
+        Rect::new(point(60, -5), point(70, 70)),
+        Rect::new(point(-1000, -1000), point(1000, 1000)),
+        Rect::new(point(-10, -10), point(-10, 10)),
+        Rect::new(point(10, 10), point(-10, -10)),
+    ];
+
+    for rect in rects {
+        let found = sorted(cells.iter_rect(rect).map(|(p, cell)| (p, cell.kind())));
+        assert_eq!(found, expected(rect), "{rect:?}");
+    }
+
+    let sand = sorted(cells.iter_kind(Sand).map(|(p, cell)| (p, cell.kind())));
+    assert_eq!(sand.len(), 4);
+    assert!(sand.iter().all(|&(_, kind)| kind == Sand));
+
+    assert_eq!(cells.count(Sand), 4);
+    assert_eq!(cells.count(Air), 0);
+    assert_eq!(
+        cells.count_kinds(),
+        HashMap::from([
+            (Sand, 4),
+            (Water, 2),
+            (Honey, 1),
+            (Bedrock, 2),
+            (PinkSand, 1)
+        ])
+    );
+    assert_eq!(
+        cells.occupied_bounds(),
+        Some(Rect::new(point(-129, -65), point(65, 65)))
+    );
+}
+
+#[test]
+fn chunked_worlds_answer_queries() {
+    check_queries(&chunked());
+}
+
+#[test]
+fn sparse_worlds_answer_queries() {
+    check_queries(&sparse());
+}
+
+#[test]
+fn dense_worlds_answer_queries() {
+    check_queries(&dense());
+}
+
+#[test]
+fn empty_worlds_have_no_bounds() {
+    let mut cells = Cells::with_seed(0);
+    assert_eq!(cells.occupied_bounds(), None);
+    assert!(cells.count_kinds().is_empty());
+
+    cells.set_cell(point(-3, 4), cell(Sand));
+    assert_eq!(
+        cells.occupied_bounds(),
+        Some(Rect::new(point(-3, 4), point(-2, 5)))
+    );
+
+    cells.erase(point(-3, 4));
+    assert_eq!(cells.occupied_bounds(), None);
+}
+
+#[test]
+fn rect_queries_match_filtering_everything() {
+    let mut rng = StdRng::seed_from_u64(12);
+
+    // Few and many chunks, so both ways of finding the chunks under a rect are used.
+    for spread in [10, 300, 3000] {
+        let mut cells = Cells::with_seed(0);
+        let mut placed = Vec::new();
+        for _ in 0..2000 {
+            let p = point(
+                rng.random_range(-spread..spread),
+                rng.random_range(-spread..spread),
+            );
+            cells.set_cell(p, cell(Sand));
+            placed.push((p.tup(), Sand));
+        }
+        placed.sort_unstable_by_key(|&(p, _)| p);
+        placed.dedup();
+
+        for _ in 0..200 {
+            let min = point(
+                rng.random_range(-spread..spread),
+                rng.random_range(-spread..spread),
+            );
+            let size = point(rng.random_range(0..300), rng.random_range(0..300));
+            let rect = Rect::new(min, min + size);
+
+            let found = sorted(cells.iter_rect(rect).map(|(p, cell)| (p, cell.kind())));
+            let filtered: Vec<_> = placed
+                .iter()
+                .copied()
+                .filter(|&((x, y), _)| rect.contains(point(x, y)))
+                .collect();
+            assert_eq!(found, filtered, "{rect:?}");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, cell},
+    point::{Rect, point},
+    reaction::{Reaction, Reactions},
+};
+
+fn sand_under_water(cells: &mut Cells) {
+    cells.fill_rect(
+        Rect::new(point(0, 0), point(20, 1)),
+        cell(Bedrock),
+        Default::default(),
+    );
+    cells.fill_rect(
+        Rect::new(point(1, 1), point(19, 4)),
+        cell(Sand),
+        Default::default(),
+    );
+    cells.fill_rect(
+        Rect::new(point(1, 4), point(19, 8)),
+        cell(Water),
+        Default::default(),
+    );
+}
+
+fn tank() -> Cells {
+    Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(20, 20)), Boundary::Wall)
+}
+
+#[test]
+fn worlds_have_no_reactions_by_default() {
+    let mut cells = tank();
+    sand_under_water(&mut cells);
+
+    cells.step(300);
+
+    assert_eq!(cells.reactions().iter().count(), 0);
+    assert_eq!(cells.count(Water), 18 * 4);
+    assert_eq!(cells.count(Sand), 18 * 3);
+    assert_eq!(cells.count(WetSand), 0);
+}
+
+#[test]
+fn water_soaks_into_sand() {
+    let mut cells = tank();
+    cells.set_reactions(Reactions::built_in());
+    sand_under_water(&mut cells);
+
+    cells.step(300);
+
+    let wet = cells.count(WetSand);
+    assert!(wet > 0);
+    assert_eq!(cells.count(Sand) + wet, 18 * 3);
+    assert_eq!(cells.count(Water) + wet, 18 * 4);
+}
+
+#[test]
+fn reaction_products_wake_up() {
+    let mut cells = tank();
+    cells.fill_rect(
+        Rect::new(point(9, 5), point(12, 6)),
+        cell(Bedrock),
+        Default::default(),
+    );
+    cells.set_cell(point(10, 6), cell(Sand));
+    cells.step(10);
+    assert_eq!(cells.awake_count(), 0);
+
+    // The grain turns into gas and a piece of the ledge it rests on melts into water, both have
+    // to move.
+    cells.add_reaction(Reaction::new((Sand, Bedrock), (Hydrogen, Water), 1.0));
+    cells.wake(point(10, 6));
+    cells.step(100);
+
+    assert_eq!(cells.count(Bedrock), 2);
+    assert_eq!(cells.count(Hydrogen), 1);
+    assert_eq!(cells.count(Water), 1);
+    let (drop, _) = cells.iter_kind(Water).next().unwrap();
+    assert_eq!(drop.tup().1, 0);
+    let (gas, _) = cells.iter_kind(Hydrogen).next().unwrap();
+    assert!(gas.tup().1 > 6);
+}
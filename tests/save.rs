@@ -0,0 +1,138 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind, CellKind::*, Cells, FORMAT_VERSION, LoadError, cell},
+    point::{Rect, point},
+    store::CellStore,
+};
+
+const KINDS: [CellKind; 11] = [
+    PurpleSand, BlueSand, Water, Honey, Sand, PinkSand, Bedrock, Hydrogen, Ice, Steam, WetSand,
+];
+
+fn random_world(rng: &mut StdRng) -> Cells {
+    let mut cells = Cells::with_seed(rng.random());
+
+    if rng.random_bool(0.5) {
+        let boundary = [Boundary::Wall, Boundary::Void, Boundary::Wrap][rng.random_range(0..3)];
+        cells = cells.with_bounds(Rect::new(point(-100, -100), point(100, 100)), boundary);
+    }
+
+    let clusters = rng.random_range(0..5);
+    for _ in 0..clusters {
+        let centre = point(rng.random_range(-90..90), rng.random_range(-90..90));
+        for _ in 0..rng.random_range(1..300) {
+            let offset = point(rng.random_range(-10..10), rng.random_range(-10..10));
+            let kind = KINDS[rng.random_range(0..KINDS.len())];
+            cells.set_cell(centre + offset, cell(kind));
+        }
+    }
+
+    for _ in 0..rng.random_range(0..20) {
+        cells.update_all();
+    }
+
+    cells
+}
+
+fn contents(cells: &Cells) -> Vec<((i32, i32), CellKind)> {
+    let mut contents: Vec<_> = cells
+        .store()
+        .iter()
+        .map(|(point, cell)| (point.tup(), cell.kind()))
+        .collect();
+    contents.sort_by_key(|&(position, _)| position);
+    contents
+}
+
+fn save(cells: &Cells) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    cells.save(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn random_worlds_round_trip() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..50 {
+        let cells = random_world(&mut rng);
+        let loaded = Cells::load(&mut save(&cells).as_slice()).unwrap();
+
+        assert_eq!(contents(&cells), contents(&loaded));
+        assert_eq!(cells.seed(), loaded.seed());
+        assert_eq!(cells.tick(), loaded.tick());
+        assert_eq!(cells.bounds(), loaded.bounds());
+    }
+}
+
+#[test]
+fn far_apart_cells_round_trip() {
+    let mut cells = Cells::with_seed(1);
+    cells.set_cell(point(-1_000_000, 3), cell(Sand));
+    cells.set_cell(point(1_000_000, -3), cell(Water));
+
+    let loaded = Cells::load(&mut save(&cells).as_slice()).unwrap();
+
+    assert_eq!(contents(&cells), contents(&loaded));
+}
+
+#[test]
+fn truncated_files_are_reported() {
+    let mut cells = Cells::with_seed(2);
+    for x in 0..10 {
+        cells.set_cell(point(x, x), cell(Honey));
+    }
+    let bytes = save(&cells);
+
+    for length in 0..bytes.len() {
+        let result = Cells::load(&mut &bytes[..length]);
+        assert!(
+            matches!(result, Err(LoadError::Truncated)),
+            "length {length} gave {result:?}"
+        );
+    }
+}
+
+#[test]
+fn empty_bounds_are_reported() {
+    let cells =
+        Cells::with_seed(2).with_bounds(Rect::new(point(-5, -5), point(5, 5)), Boundary::Wrap);
+    let bytes = save(&cells);
+
+    // The four coordinates of the bounds follow the header and the bounds flag.
+    for rect in [
+        Rect::new(point(0, 0), point(0, 0)),
+        Rect::new(point(3, -5), point(3, 5)),
+        Rect::new(point(5, 5), point(-5, -5)),
+    ] {
+        let (min, max) = (rect.min().tup(), rect.max().tup());
+        let mut bytes = bytes.clone();
+        for (i, value) in [min.0, min.1, max.0, max.1].into_iter().enumerate() {
+            let at = 23 + 4 * i;
+            bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let result = Cells::load(&mut bytes.as_slice());
+        assert!(
+            matches!(result, Err(LoadError::Malformed("empty bounds"))),
+            "bounds {rect:?} gave {result:?}"
+        );
+    }
+}
+
+#[test]
+fn future_versions_are_rejected() {
+    let mut bytes = save(&Cells::with_seed(3));
+    bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+    let result = Cells::load(&mut bytes.as_slice());
+    assert!(
+        matches!(result, Err(LoadError::UnsupportedVersion(version)) if version == FORMAT_VERSION + 1)
+    );
+}
+
+#[test]
+fn other_files_are_rejected() {
+    let result = Cells::load(&mut b"not a world at all".as_slice());
+    assert!(matches!(result, Err(LoadError::NotAWorld)));
+}
@@ -0,0 +1,112 @@
+use std::thread;
+
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, Event, Schedule, cell},
+    point::{Rect, point},
+};
+
+/// Runs `test` on a thread with a deliberately small stack, so anything that recurses once per
+/// cell overflows long before the world gets big.
+fn with_small_stack(test: impl FnOnce() + Send + 'static) {
+    thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(test)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn tall_column_updates_without_overflowing() {
+    with_small_stack(|| {
+        let mut cells = Cells::with_seed(0);
+
+        cells.set_cell(point(0, -1), cell(Bedrock));
+        for y in 0..10_000 {
+            cells.set_cell(point(0, y), cell(Water));
+        }
+
+        cells.update_all();
+
+        let water = (-2..10_000)
+            .flat_map(|y| (-2..=2).map(move |x| point(x, y)))
+            .filter(|&p| cells.cell_at(p).is(&[Water]))
+            .count();
+
+        assert_eq!(water, 10_000);
+    });
+}
+
+/// Where each cell that moved during the next tick was when it was visited, in visiting order.
+fn visit_order(cells: &mut Cells) -> Vec<(i32, i32)> {
+    cells.update_all();
+    cells
+        .drain_events()
+        .map(|event| match event {
+            Event::Moved { from, .. } => from.tup(),
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn scanlines_go_bottom_up_and_alternate_direction() {
+    let mut cells = Cells::with_seed(0);
+    cells.set_schedule(Schedule::Scanline);
+
+    // Two rows of falling sand, spaced out so no grain waits on another.
+    for x in [0, 2, 4] {
+        cells.set_cell(point(x, 10), cell(Sand));
+    }
+    for x in [1, 3] {
+        cells.set_cell(point(x, 20), cell(Sand));
+    }
+    cells.record_events();
+
+    assert_eq!(
+        visit_order(&mut cells),
+        [(0, 10), (2, 10), (4, 10), (1, 20), (3, 20)]
+    );
+    assert_eq!(
+        visit_order(&mut cells),
+        [(4, 9), (2, 9), (0, 9), (3, 19), (1, 19)]
+    );
+    assert_eq!(
+        visit_order(&mut cells),
+        [(0, 8), (2, 8), (4, 8), (1, 18), (3, 18)]
+    );
+}
+
+/// A seeded pile poured from a single point, after it has had time to settle.
+fn scanline_pile(seed: u64) -> Vec<(i32, i32)> {
+    let mut cells =
+        Cells::with_seed(seed).with_bounds(Rect::new(point(-40, 0), point(41, 30)), Boundary::Wall);
+    cells.set_schedule(Schedule::Scanline);
+
+    for _ in 0..200 {
+        cells.set_cell(point(0, 29), cell(Sand));
+        cells.update_all();
+    }
+    cells.step(200);
+
+    let mut pile: Vec<_> = cells.iter().map(|(p, _)| p.tup()).collect();
+    pile.sort_unstable();
+    pile
+}
+
+#[test]
+fn scanline_runs_are_reproducible() {
+    assert_eq!(scanline_pile(3), scanline_pile(3));
+}
+
+#[test]
+fn scanline_piles_are_not_lopsided() {
+    let pile = scanline_pile(3);
+    let left = pile.iter().filter(|&&(x, _)| x < 0).count() as i32;
+    let right = pile.iter().filter(|&&(x, _)| x > 0).count() as i32;
+
+    assert!(
+        (left - right).abs() <= pile.len() as i32 / 10,
+        "{left} left, {right} right"
+    );
+}
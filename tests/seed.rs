@@ -0,0 +1,45 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, Cell, CellKind::*, Cells, cell},
+    point::{Rect, point},
+};
+
+/// A world built from `seed` with the same edits made every time.
+fn run(seed: u64) -> Cells {
+    let mut cells = Cells::with_seed(seed)
+        .with_bounds(Rect::new(point(-40, -60), point(40, 5)), Boundary::Wall);
+    for y in 1..6 {
+        for x in -20..20 {
+            cells.set_cell(point(2 * x + y % 2, -10 * y), cell(Bedrock));
+        }
+    }
+
+    for tick in 0..150 {
+        let kind = [Water, Sand, Hydrogen, Honey][tick % 4];
+        for x in -1..=1 {
+            cells.set_cell(point(x, 0), cell(kind));
+        }
+        cells.update_all();
+    }
+
+    cells
+}
+
+fn sorted(cells: &Cells) -> Vec<((i32, i32), Cell)> {
+    let mut all: Vec<_> = cells.iter().map(|(p, cell)| (p.tup(), cell)).collect();
+    all.sort_by_key(|&(p, _)| p);
+    all
+}
+
+#[test]
+fn same_seed_same_world() {
+    for seed in [0, 1, 42] {
+        let (a, b) = (run(seed), run(seed));
+        assert_eq!(sorted(&a), sorted(&b));
+        assert_eq!(a.tick(), b.tick());
+    }
+}
+
+#[test]
+fn different_seeds_different_worlds() {
+    assert_ne!(sorted(&run(1)), sorted(&run(2)));
+}
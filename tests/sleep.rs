@@ -0,0 +1,40 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind::*, Cells, cell},
+    point::{Rect, point},
+};
+
+fn container() -> Cells {
+    Cells::with_seed(0).with_bounds(Rect::new(point(0, 0), point(30, 40)), Boundary::Wall)
+}
+
+#[test]
+fn a_settled_pile_costs_nothing() {
+    let mut cells = container();
+    cells.fill_rect(
+        Rect::new(point(12, 20), point(18, 40)),
+        cell(Sand),
+        Default::default(),
+    );
+
+    cells.step(500);
+
+    assert_eq!(cells.awake_count(), 0);
+    assert_eq!(cells.count(Sand), 6 * 20);
+}
+
+#[test]
+fn disturbing_a_settled_pile_wakes_it() {
+    let mut cells = container();
+    cells.fill_rect(
+        Rect::new(point(12, 20), point(18, 40)),
+        cell(Sand),
+        Default::default(),
+    );
+    cells.step(500);
+
+    cells.erase(point(15, 0));
+    assert!(cells.awake_count() > 0);
+
+    cells.step(500);
+    assert_eq!(cells.awake_count(), 0);
+}
@@ -0,0 +1,174 @@
+use the_pink_sand_simulator::{
+    cells::{CellKind::*, Cells, cell},
+    point::{Rect, point},
+    stamp::{Blend, Stamp},
+};
+
+/// A 2 wide, 3 high stamp with a different kind in each corner:
+///
+/// ```text
+/// W .
+/// . .
+/// S B
+/// ```
+fn corners() -> Stamp {
+    let mut stamp = Stamp::new(2, 3);
+    stamp.set_kind(point(0, 0), Sand);
+    stamp.set_kind(point(1, 0), Bedrock);
+    stamp.set_kind(point(0, 2), Water);
+    stamp
+}
+
+#[test]
+fn quarter_turns_go_anticlockwise() {
+    let turned = corners().rotate(1);
+
+    assert_eq!((turned.width(), turned.height()), (3, 2));
+    assert_eq!(turned.kind_at(point(2, 0)), Sand);
+    assert_eq!(turned.kind_at(point(2, 1)), Bedrock);
+    assert_eq!(turned.kind_at(point(0, 0)), Water);
+}
+
+#[test]
+fn half_turns_flip_both_ways() {
+    let turned = corners().rotate(2);
+
+    assert_eq!((turned.width(), turned.height()), (2, 3));
+    assert_eq!(turned.kind_at(point(1, 2)), Sand);
+    assert_eq!(turned.kind_at(point(0, 2)), Bedrock);
+    assert_eq!(turned.kind_at(point(1, 0)), Water);
+    assert_eq!(turned, corners().flip_horizontal().flip_vertical());
+}
+
+#[test]
+fn three_quarter_turns_go_clockwise() {
+    let turned = corners().rotate(3);
+
+    assert_eq!((turned.width(), turned.height()), (3, 2));
+    assert_eq!(turned.kind_at(point(0, 1)), Sand);
+    assert_eq!(turned.kind_at(point(0, 0)), Bedrock);
+    assert_eq!(turned.kind_at(point(2, 1)), Water);
+}
+
+#[test]
+fn turns_wrap_around() {
+    assert_eq!(corners().rotate(0), corners());
+    assert_eq!(corners().rotate(4), corners());
+    assert_eq!(corners().rotate(-1), corners().rotate(3));
+    assert_eq!(corners().rotate(-2), corners().rotate(2));
+    assert_eq!(corners().rotate(-7), corners().rotate(1));
+    assert_eq!(corners().rotate(1).rotate(-1), corners());
+}
+
+#[test]
+fn flips_mirror_one_axis() {
+    let horizontal = corners().flip_horizontal();
+    assert_eq!(horizontal.kind_at(point(1, 0)), Sand);
+    assert_eq!(horizontal.kind_at(point(0, 0)), Bedrock);
+    assert_eq!(horizontal.kind_at(point(1, 2)), Water);
+    assert_eq!(horizontal.flip_horizontal(), corners());
+
+    let vertical = corners().flip_vertical();
+    assert_eq!(vertical.kind_at(point(0, 2)), Sand);
+    assert_eq!(vertical.kind_at(point(1, 2)), Bedrock);
+    assert_eq!(vertical.kind_at(point(0, 0)), Water);
+    assert_eq!(vertical.flip_vertical(), corners());
+}
+
+#[test]
+fn copies_paste_back_elsewhere() {
+    let mut cells = Cells::with_seed(0);
+    cells.paste(&corners(), point(-20, -20), Blend::Overwrite);
+
+    let copied = cells.copy(Rect::new(point(-20, -20), point(-18, -17)));
+    assert_eq!(copied, corners());
+
+    cells.paste(&copied, point(30, 40), Blend::Overwrite);
+    assert_eq!(cells.cell_at(point(30, 40)).kind(), Sand);
+    assert_eq!(cells.cell_at(point(31, 40)).kind(), Bedrock);
+    assert_eq!(cells.cell_at(point(30, 42)).kind(), Water);
+    assert_eq!(cells.count(Sand), 2);
+}
+
+#[test]
+fn copies_keep_temperature() {
+    let mut cells = Cells::with_seed(0);
+    cells.set_cell(point(0, 0), cell(Honey).with_temperature(-5.0));
+
+    let copied = cells.copy(Rect::new(point(0, 0), point(1, 1)));
+    assert_eq!(copied.cell_at(point(0, 0)).temperature(), -5.0);
+
+    cells.paste(&copied, point(10, 0), Blend::Overwrite);
+    assert_eq!(cells.temperature_at(point(10, 0)), -5.0);
+}
+
+/// A row of honey under a row of pink sand, pasted over with `corners` at the origin.
+fn blended(blend: Blend) -> Cells {
+    let mut cells = Cells::with_seed(0);
+    cells.fill_rect(
+        Rect::new(point(0, 0), point(2, 1)),
+        cell(Honey),
+        Default::default(),
+    );
+    cells.fill_rect(
+        Rect::new(point(0, 1), point(2, 2)),
+        cell(PinkSand),
+        Default::default(),
+    );
+    cells.paste(&corners(), point(0, 0), blend);
+    cells
+}
+
+#[test]
+fn overwriting_writes_air_too() {
+    let cells = blended(Blend::Overwrite);
+
+    assert_eq!(cells.cell_at(point(0, 0)).kind(), Sand);
+    assert_eq!(cells.cell_at(point(1, 0)).kind(), Bedrock);
+    assert!(cells.cell_at(point(0, 1)).is_air());
+    assert!(cells.cell_at(point(1, 1)).is_air());
+    assert_eq!(cells.cell_at(point(0, 2)).kind(), Water);
+}
+
+#[test]
+fn blending_into_air_keeps_the_world() {
+    let cells = blended(Blend::IntoAir);
+
+    assert_eq!(cells.cell_at(point(0, 0)).kind(), Honey);
+    assert_eq!(cells.cell_at(point(1, 0)).kind(), Honey);
+    assert_eq!(cells.cell_at(point(0, 1)).kind(), PinkSand);
+    assert_eq!(cells.cell_at(point(0, 2)).kind(), Water);
+}
+
+#[test]
+fn skipping_air_keeps_the_world_under_it() {
+    let cells = blended(Blend::SkipAir);
+
+    assert_eq!(cells.cell_at(point(0, 0)).kind(), Sand);
+    assert_eq!(cells.cell_at(point(1, 0)).kind(), Bedrock);
+    assert_eq!(cells.cell_at(point(0, 1)).kind(), PinkSand);
+    assert_eq!(cells.cell_at(point(1, 1)).kind(), PinkSand);
+    assert_eq!(cells.cell_at(point(0, 2)).kind(), Water);
+}
+
+#[test]
+fn pasting_wakes_what_it_changes() {
+    let mut stamp = Stamp::new(4, 4);
+    for offset in Rect::new(point(0, 0), point(4, 4)).points() {
+        stamp.set_kind(offset, Bedrock);
+    }
+
+    let mut cells = Cells::with_seed(0);
+    cells.paste(&stamp, point(0, 0), Blend::Overwrite);
+    assert!(cells.awake_count() > 0);
+
+    cells.step(5);
+    assert_eq!(cells.awake_count(), 0);
+
+    // Nothing changes, so nothing wakes.
+    cells.paste(&stamp, point(0, 0), Blend::Overwrite);
+    assert_eq!(cells.awake_count(), 0);
+
+    cells.paste(&corners(), point(1, 4), Blend::SkipAir);
+    assert!(cells.awake_count() > 0);
+}
@@ -0,0 +1,125 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, CellKind, CellKind::*, Cells, cell},
+    point::{Rect, point},
+    store::{CellStore, ChunkStore, DenseStore, SparseStore},
+};
+
+/// The dense store's rectangle, and the bounds of every world here.
+const RECT: Rect = Rect::new(point(-70, -10), point(70, 60));
+
+/// A seeded mix of every kind that moves, with a bedrock shelf, run for 100 ticks.
+fn run<S: CellStore>(store: S) -> Vec<((i32, i32), CellKind)> {
+    let mut cells = Cells::with_store(store, 9).with_bounds(RECT, Boundary::Wall);
+
+    cells.fill_rect(
+        Rect::new(point(-40, 10), point(10, 12)),
+        cell(Bedrock),
+        Default::default(),
+    );
+    let kinds = [Sand, Water, Honey, Hydrogen, PinkSand, Steam];
+    for (i, kind) in kinds.into_iter().enumerate() {
+        let x = -60 + 20 * i as i32;
+        cells.fill_rect(
+            Rect::new(point(x, 20), point(x + 15, 30)),
+            cell(kind),
+            Default::default(),
+        );
+    }
+    cells.step(100);
+
+    let mut contents: Vec<_> = cells
+        .iter()
+        .map(|(p, cell)| (p.tup(), cell.kind()))
+        .collect();
+    contents.sort_unstable_by_key(|&(p, _)| p);
+    contents
+}
+
+#[test]
+fn every_store_simulates_the_same_world() {
+    let chunked = run(ChunkStore::default());
+
+    assert_eq!(run(SparseStore::default()), chunked);
+    assert_eq!(run(DenseStore::new(RECT)), chunked);
+}
+
+/// Swaps across the chunk edge at the origin and a cell with air, checking what each store holds.
+fn check_swaps<S: CellStore>(mut store: S) {
+    store.set_cell(point(-1, -1), cell(Sand));
+    store.set_cell(point(0, 0), cell(Water));
+
+    store.swap_cells(point(-1, -1), point(0, 0));
+    assert_eq!(store.cell_at(point(-1, -1)).kind(), Water);
+    assert_eq!(store.cell_at(point(0, 0)).kind(), Sand);
+
+    store.swap_cells(point(0, 0), point(5, 5));
+    assert!(store.cell_at(point(0, 0)).is_air());
+    assert_eq!(store.cell_at(point(5, 5)).kind(), Sand);
+    assert_eq!(store.len(), 2);
+}
+
+#[test]
+fn stores_swap_alike() {
+    check_swaps(ChunkStore::default());
+    check_swaps(SparseStore::default());
+    check_swaps(DenseStore::new(RECT));
+}
+
+#[test]
+fn dense_stores_drop_what_leaves_their_rect() {
+    let mut store = DenseStore::new(RECT);
+    let (edge, outside) = (point(69, 0), point(70, 0));
+
+    store.set_cell(outside, cell(Sand));
+    assert!(store.is_empty());
+
+    store.set_cell(edge, cell(Sand));
+    store.swap_cells(edge, outside);
+    assert!(store.cell_at(edge).is_air());
+    assert!(store.cell_at(outside).is_air());
+    assert!(store.is_empty());
+
+    // Swapping in from outside brings air.
+    store.set_cell(edge, cell(Water));
+    store.swap_cells(outside, edge);
+    assert!(store.cell_at(edge).is_air());
+}
+
+#[test]
+fn dense_stores_iterate_their_cells() {
+    let mut store = DenseStore::new(RECT);
+    for p in [point(-70, -10), point(-1, 0), point(0, 0), point(69, 59)] {
+        store.set_cell(p, cell(Honey));
+    }
+
+    let mut all: Vec<_> = store.iter().map(|(p, _)| p.tup()).collect();
+    all.sort_unstable();
+    assert_eq!(all, [(-70, -10), (-1, 0), (0, 0), (69, 59)]);
+    assert_eq!(store.len(), 4);
+
+    let mut corner: Vec<_> = store
+        .iter_rect(Rect::new(point(-100, -100), point(0, 1)))
+        .map(|(p, _)| p.tup())
+        .collect();
+    corner.sort_unstable();
+    assert_eq!(corner, [(-70, -10), (-1, 0)]);
+
+    let outside = Rect::new(point(70, 0), point(100, 100));
+    assert_eq!(store.iter_rect(outside).count(), 0);
+}
+
+#[test]
+fn dense_worlds_keep_to_their_bounds() {
+    let mut cells = Cells::dense(RECT, Boundary::Void);
+    cells.fill_rect(
+        Rect::new(point(-5, 40), point(5, 80)),
+        cell(Sand),
+        Default::default(),
+    );
+    assert_eq!(cells.count(Sand), 10 * 20);
+
+    // Everything falls out of the bottom.
+    cells.step(150);
+    assert_eq!(cells.count(Sand), 0);
+    assert!(cells.store().is_empty());
+}
@@ -0,0 +1,81 @@
+use the_pink_sand_simulator::{
+    cells::{Boundary, Cell, CellKind::*, Cells, cell},
+    point::{Rect, point},
+};
+
+fn tank() -> Cells {
+    Cells::with_seed(0).with_bounds(Rect::new(point(-50, 0), point(50, 50)), Boundary::Wall)
+}
+
+fn pool(cells: &mut Cells, fluid: Cell) {
+    cells.fill_rect(
+        Rect::new(point(-5, 0), point(5, 4)),
+        fluid,
+        Default::default(),
+    );
+}
+
+#[test]
+fn cold_water_freezes_after_a_delay() {
+    let mut cells = tank();
+    pool(&mut cells, cell(Water).with_temperature(-20.0));
+
+    cells.step(10);
+    assert_eq!(cells.count(Ice), 0);
+
+    cells.step(40);
+    assert_eq!(cells.count(Water), 0);
+    assert_eq!(cells.count(Ice), 10 * 4);
+}
+
+#[test]
+fn warm_ice_melts() {
+    let mut cells = tank();
+    pool(&mut cells, cell(Ice).with_temperature(30.0));
+
+    cells.step(50);
+
+    assert_eq!(cells.count(Ice), 0);
+    assert_eq!(cells.count(Water), 10 * 4);
+}
+
+#[test]
+fn hot_water_boils_and_rises() {
+    let mut cells = tank();
+    pool(&mut cells, cell(Water).with_temperature(200.0));
+
+    cells.step(60);
+
+    assert_eq!(cells.count(Water), 0);
+    assert_eq!(cells.count(Steam), 10 * 4);
+    assert!(cells.iter_kind(Steam).all(|(p, _)| p.tup().1 > 4));
+}
+
+#[test]
+fn cool_steam_condenses() {
+    let mut cells = tank();
+    pool(&mut cells, cell(Steam));
+
+    cells.step(60);
+
+    assert_eq!(cells.count(Steam), 0);
+    assert_eq!(cells.count(Water), 10 * 4);
+}
+
+#[test]
+fn cold_honey_heaps_up() {
+    let width = |temperature| {
+        let mut cells = tank();
+        cells.fill_rect(
+            Rect::new(point(-3, 0), point(3, 20)),
+            cell(Honey).with_temperature(temperature),
+            Default::default(),
+        );
+        cells.step(300);
+
+        let xs: Vec<i32> = cells.iter_kind(Honey).map(|(p, _)| p.tup().0).collect();
+        xs.iter().max().unwrap() - xs.iter().min().unwrap()
+    };
+
+    assert!(width(0.0) * 2 < width(20.0));
+}